@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS book_invites (
+	"code"			TEXT NOT NULL DEFAULT upper(substr(md5((gen_random_uuid())::text), 1, 8)),
+	"book_id"		INTEGER NOT NULL REFERENCES books(id),
+	"created_by"	INTEGER NOT NULL REFERENCES users(id),
+	"expires_at"	TIMESTAMP,
+	"max_uses"		INTEGER,
+	"uses"			INTEGER NOT NULL DEFAULT 0,
+	"created_at"	TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY ("code")
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS join_requests (
+	"id"			SERIAL NOT NULL PRIMARY KEY,
+	"book_id"		INTEGER NOT NULL REFERENCES books(id),
+	"user_id"		INTEGER NOT NULL REFERENCES users(id),
+	"status"		TEXT NOT NULL DEFAULT 'pending',
+	"resolved_by"	INTEGER REFERENCES users(id),
+	"created_at"	TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	"resolved_at"	TIMESTAMP,
+	UNIQUE ("book_id", "user_id")
+);
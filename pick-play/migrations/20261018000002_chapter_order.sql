@@ -0,0 +1,16 @@
+ALTER TABLE chapters ADD COLUMN IF NOT EXISTS "position" INTEGER;
+
+UPDATE chapters AS c
+SET "position" = ordered.position
+FROM (
+	SELECT id, ROW_NUMBER() OVER (PARTITION BY book_id ORDER BY created_at, id) AS position
+	FROM chapters
+) AS ordered
+WHERE c.id = ordered.id;
+
+ALTER TABLE chapters ALTER COLUMN "position" SET NOT NULL;
+
+-- Entries without a chapter predate every chapter and count towards all snapshots
+ALTER TABLE added_points
+	ADD COLUMN IF NOT EXISTS "chapter_id" INTEGER REFERENCES chapters(id),
+	ADD COLUMN IF NOT EXISTS "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP;
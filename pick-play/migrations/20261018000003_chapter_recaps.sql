@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS chapter_recaps (
+	"chapter_id"	INTEGER NOT NULL PRIMARY KEY REFERENCES chapters(id),
+	"book_id"		INTEGER NOT NULL REFERENCES books(id),
+	"contents"		JSONB NOT NULL,
+	"created_at"	TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
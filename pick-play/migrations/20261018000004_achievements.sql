@@ -0,0 +1,10 @@
+CREATE TYPE badges AS ENUM ('chapter_win', 'hat_trick', 'perfect_week', 'bonus_top_score');
+
+CREATE TABLE IF NOT EXISTS achievements (
+	"book_id"		INTEGER NOT NULL REFERENCES books(id),
+	"user_id"		INTEGER NOT NULL REFERENCES users(id),
+	"chapter_id"	INTEGER NOT NULL REFERENCES chapters(id),
+	"badge"			badges NOT NULL,
+	"created_at"	TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (user_id, chapter_id, badge)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS grading_changes (
+	"id"					SERIAL NOT NULL PRIMARY KEY,
+	"book_id"				INTEGER NOT NULL REFERENCES books(id),
+	"chapter_id"			INTEGER NOT NULL REFERENCES chapters(id),
+	"user_id"				INTEGER NOT NULL REFERENCES users(id),
+	"events"				JSONB NOT NULL,
+	"point_deltas"			JSONB NOT NULL,
+	"reverted_change_id"	INTEGER REFERENCES grading_changes(id),
+	"created_at"			TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS pick_revisions (
+	"id"			SERIAL NOT NULL PRIMARY KEY,
+	"book_id"		INTEGER NOT NULL REFERENCES books(id),
+	"chapter_id"	INTEGER NOT NULL REFERENCES chapters(id),
+	"event_id"		INTEGER NOT NULL REFERENCES events(id),
+	"user_id"		INTEGER NOT NULL REFERENCES users(id),
+	"choice"		JSONB NOT NULL,
+	"wager"			JSONB NOT NULL,
+	"created_at"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS pick_revisions_chapter_user ON pick_revisions (chapter_id, user_id);
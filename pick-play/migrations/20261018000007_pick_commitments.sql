@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS pick_commitments (
+	"chapter_id"	INTEGER NOT NULL REFERENCES chapters(id),
+	"user_id"		INTEGER NOT NULL REFERENCES users(id),
+	"payload"		TEXT NOT NULL,
+	"hash"			TEXT NOT NULL,
+	PRIMARY KEY (chapter_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS chapter_commitments (
+	"chapter_id"	INTEGER NOT NULL PRIMARY KEY REFERENCES chapters(id),
+	"root"			TEXT NOT NULL,
+	"created_at"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
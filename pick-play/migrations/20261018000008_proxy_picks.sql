@@ -0,0 +1,6 @@
+ALTER TABLE IF EXISTS picks
+ADD COLUMN IF NOT EXISTS "entered_by"	INTEGER REFERENCES users(id),
+ADD COLUMN IF NOT EXISTS "entered_at"	TIMESTAMP;
+
+ALTER TABLE IF EXISTS pick_revisions
+ADD COLUMN IF NOT EXISTS "entered_by"	INTEGER REFERENCES users(id);
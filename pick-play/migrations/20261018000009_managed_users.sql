@@ -0,0 +1,2 @@
+ALTER TABLE IF EXISTS users
+ADD COLUMN IF NOT EXISTS "managed_by"	INTEGER REFERENCES users(id);
@@ -0,0 +1,5 @@
+ALTER TABLE IF EXISTS books
+ADD COLUMN IF NOT EXISTS "max_entries"	INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE IF EXISTS users
+ADD COLUMN IF NOT EXISTS "entry_name"	TEXT;
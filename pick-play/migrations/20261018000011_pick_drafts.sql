@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS pick_drafts (
+	"chapter_id"	INTEGER NOT NULL REFERENCES chapters(id),
+	"user_id"		INTEGER NOT NULL REFERENCES users(id),
+	"contents"		JSONB NOT NULL,
+	"updated_at"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY ("chapter_id", "user_id")
+);
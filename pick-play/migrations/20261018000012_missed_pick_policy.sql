@@ -0,0 +1,19 @@
+CREATE TYPE missed_pick_policies AS ENUM ('none', 'random', 'favorites', 'carry_forward', 'minimum_score', 'average_score');
+
+ALTER TABLE IF EXISTS books
+ADD COLUMN IF NOT EXISTS "missed_pick_policy"	missed_pick_policies NOT NULL DEFAULT 'none';
+
+-- Set on picks and points generated for members that missed a chapter
+ALTER TABLE IF EXISTS picks
+ADD COLUMN IF NOT EXISTS "auto_policy"	missed_pick_policies;
+
+ALTER TABLE IF EXISTS added_points
+ADD COLUMN IF NOT EXISTS "auto_policy"	missed_pick_policies;
+
+CREATE TABLE IF NOT EXISTS missed_picks (
+	"chapter_id"	INTEGER NOT NULL REFERENCES chapters(id),
+	"user_id"		INTEGER NOT NULL REFERENCES users(id),
+	"policy"		missed_pick_policies NOT NULL,
+	"created_at"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY ("chapter_id", "user_id")
+);
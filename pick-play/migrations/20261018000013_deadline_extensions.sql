@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS deadline_extensions (
+	"chapter_id"	INTEGER NOT NULL REFERENCES chapters(id),
+	"user_id"		INTEGER NOT NULL REFERENCES users(id),
+	"expires_at"	TIMESTAMP NOT NULL,
+	"granted_by"	INTEGER NOT NULL REFERENCES users(id),
+	"created_at"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY ("chapter_id", "user_id")
+);
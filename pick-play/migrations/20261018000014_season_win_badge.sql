@@ -0,0 +1 @@
+ALTER TYPE badges ADD VALUE IF NOT EXISTS 'season_win' BEFORE 'chapter_win';
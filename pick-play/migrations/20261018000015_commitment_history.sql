@@ -0,0 +1,18 @@
+-- Every lock and late submission adds a commitment instead of replacing the last one
+ALTER TABLE chapter_commitments DROP CONSTRAINT IF EXISTS chapter_commitments_pkey;
+ALTER TABLE chapter_commitments
+	ADD COLUMN IF NOT EXISTS "id" SERIAL PRIMARY KEY,
+	ADD COLUMN IF NOT EXISTS "late_user_id" INTEGER REFERENCES users(id);
+
+ALTER TABLE pick_commitments ADD COLUMN IF NOT EXISTS "commitment_id" INTEGER REFERENCES chapter_commitments(id);
+
+UPDATE pick_commitments AS p
+SET commitment_id = c.id
+FROM chapter_commitments AS c
+WHERE c.chapter_id = p.chapter_id;
+
+DELETE FROM pick_commitments WHERE commitment_id IS NULL;
+
+ALTER TABLE pick_commitments ALTER COLUMN commitment_id SET NOT NULL;
+ALTER TABLE pick_commitments DROP CONSTRAINT IF EXISTS pick_commitments_pkey;
+ALTER TABLE pick_commitments ADD PRIMARY KEY ("commitment_id", "user_id");
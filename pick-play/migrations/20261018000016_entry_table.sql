@@ -0,0 +1,66 @@
+-- Extra entries belong to a member's subscription instead of being users of their own
+CREATE TABLE IF NOT EXISTS book_entries (
+	"id"			SERIAL NOT NULL PRIMARY KEY,
+	"book_id"		INTEGER NOT NULL REFERENCES books(id),
+	"user_id"		INTEGER NOT NULL REFERENCES users(id),
+	"name"			TEXT NOT NULL,
+	"created_at"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	UNIQUE ("book_id", "user_id", "name")
+);
+
+-- A NULL entry is the member's own
+ALTER TABLE picks ADD COLUMN IF NOT EXISTS "entry_id" INTEGER REFERENCES book_entries(id);
+ALTER TABLE pick_drafts ADD COLUMN IF NOT EXISTS "entry_id" INTEGER REFERENCES book_entries(id);
+ALTER TABLE pick_revisions ADD COLUMN IF NOT EXISTS "entry_id" INTEGER REFERENCES book_entries(id);
+ALTER TABLE pick_commitments ADD COLUMN IF NOT EXISTS "entry_id" INTEGER REFERENCES book_entries(id);
+
+ALTER TABLE picks DROP CONSTRAINT IF EXISTS picks_book_id_chapter_id_event_id_user_id_key;
+ALTER TABLE pick_drafts DROP CONSTRAINT IF EXISTS pick_drafts_pkey;
+ALTER TABLE pick_commitments DROP CONSTRAINT IF EXISTS pick_commitments_pkey;
+
+-- Move entries that were made as users over to their owner
+INSERT INTO book_entries (book_id, user_id, name)
+SELECT s.book_id, u.managed_by, u.entry_name
+FROM users AS u
+JOIN subscriptions AS s ON u.id = s.user_id
+WHERE u.entry_name IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+CREATE TEMPORARY TABLE moved_entries AS
+SELECT u.id AS old_user_id, e.id AS entry_id, e.user_id AS owner_id
+FROM users AS u
+JOIN subscriptions AS s ON u.id = s.user_id
+JOIN book_entries AS e ON e.book_id = s.book_id AND e.user_id = u.managed_by AND e.name = u.entry_name
+WHERE u.entry_name IS NOT NULL;
+
+UPDATE picks AS p SET user_id = m.owner_id, entry_id = m.entry_id
+FROM moved_entries AS m WHERE p.user_id = m.old_user_id;
+UPDATE pick_drafts AS d SET user_id = m.owner_id, entry_id = m.entry_id
+FROM moved_entries AS m WHERE d.user_id = m.old_user_id;
+UPDATE pick_revisions AS r SET user_id = m.owner_id, entry_id = m.entry_id
+FROM moved_entries AS m WHERE r.user_id = m.old_user_id;
+UPDATE pick_commitments AS c SET user_id = m.owner_id, entry_id = m.entry_id
+FROM moved_entries AS m WHERE c.user_id = m.old_user_id;
+
+DROP TABLE moved_entries;
+
+-- Everything else an entry user could have is member-level and is dropped with it
+UPDATE chapter_commitments SET late_user_id = NULL
+WHERE late_user_id IN (SELECT id FROM users WHERE entry_name IS NOT NULL);
+DELETE FROM missed_picks WHERE user_id IN (SELECT id FROM users WHERE entry_name IS NOT NULL);
+DELETE FROM added_points WHERE user_id IN (SELECT id FROM users WHERE entry_name IS NOT NULL);
+DELETE FROM achievements WHERE user_id IN (SELECT id FROM users WHERE entry_name IS NOT NULL);
+DELETE FROM deadline_extensions WHERE user_id IN (SELECT id FROM users WHERE entry_name IS NOT NULL);
+DELETE FROM subscription_groups WHERE user_id IN (SELECT id FROM users WHERE entry_name IS NOT NULL);
+DELETE FROM join_requests WHERE user_id IN (SELECT id FROM users WHERE entry_name IS NOT NULL);
+DELETE FROM subscriptions WHERE user_id IN (SELECT id FROM users WHERE entry_name IS NOT NULL);
+DELETE FROM users WHERE entry_name IS NOT NULL;
+
+ALTER TABLE users DROP COLUMN IF EXISTS entry_name;
+
+ALTER TABLE picks
+ADD CONSTRAINT picks_entry_event_key UNIQUE NULLS NOT DISTINCT (book_id, chapter_id, event_id, user_id, entry_id);
+ALTER TABLE pick_drafts
+ADD CONSTRAINT pick_drafts_entry_key UNIQUE NULLS NOT DISTINCT (chapter_id, user_id, entry_id);
+ALTER TABLE pick_commitments
+ADD CONSTRAINT pick_commitments_entry_key UNIQUE NULLS NOT DISTINCT (commitment_id, user_id, entry_id);
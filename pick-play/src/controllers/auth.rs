@@ -98,7 +98,7 @@ impl AuthnBackend for BackendPgDB {
         let res = sqlx::query!(
             "SELECT *
             FROM users
-            WHERE username = $1",
+            WHERE username = $1 AND managed_by IS NULL",
             creds.username
         )
         .fetch_optional(&self.0)
@@ -123,7 +123,7 @@ impl AuthnBackend for BackendPgDB {
         sqlx::query!(
             "SELECT *
             FROM users
-            WHERE id = $1",
+            WHERE id = $1 AND managed_by IS NULL",
             user_id
         )
         .fetch_optional(pool)
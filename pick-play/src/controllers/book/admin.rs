@@ -9,10 +9,20 @@ use reqwest::StatusCode;
 use crate::{
     auth::AuthSession,
     model::{
-        book::{BookSubscription, get_book_members, search_users_not_in_book, add_user_to_book, remove_user_from_book, delete_book_cascade},
-        chapter::chapters_with_stats,
+        book::{
+            add_points as add_book_points, add_user_to_book, delete_book_cascade, get_book,
+            get_book_members, remove_user_from_book, search_users_not_in_book, set_member_role,
+            BookCapability, BookMember, BookRole, BookSubscription,
+        },
+        chapter::{chapters_with_stats, get_chapters},
+        entry::{max_entries as get_max_entries, set_max_entries},
+        group::{add_group_member, get_book_groups, remove_group_member},
+        invite::{create_invite, delete_invite, get_book_invites},
+        join_request::{pending_book_requests, resolve_join_request},
+        managed::{book_managed_users, create_managed_user},
+        missed_pick::{book_policy, set_book_policy, MissedPickPolicy},
     },
-    AppError, AppStateRef,
+    AppError, AppNotification, AppStateRef,
 };
 
 pub async fn handler(
@@ -27,6 +37,10 @@ pub async fn handler(
         .map_err(AppError::from)?;
 
     let chapters = chapters_with_stats(user.id, book_subscription.id, pool).await?;
+    let invites = get_book_invites(book_subscription.id, pool).await?;
+    let join_requests = pending_book_requests(book_subscription.id, pool).await?;
+    let groups = get_book_groups(book_subscription.id, pool).await?;
+
     let unpublished_chapters = chapters
         .iter()
         .filter(|chapter| !chapter.is_visible)
@@ -37,6 +51,9 @@ pub async fn handler(
         &book_subscription,
         unpublished_chapters,
         &members,
+        &invites,
+        &join_requests,
+        &groups,
     ))
 }
 
@@ -61,6 +78,7 @@ pub async fn add_user(
     Ok(crate::view::book::admin::new_member_row(
         user_params.user_id,
         &user_params.username,
+        book_subscription.role.is_admin(),
     ))
 }
 
@@ -101,9 +119,25 @@ pub async fn remove_user(
     State(state): State<AppStateRef>,
     book: Extension<BookSubscription>,
     form: Form<RemoveUserForm>,
-) -> Result<(), AppError<'static>> {
+) -> Result<(), RespErr> {
     let pool = &state.pool;
 
+    let member = get_book(form.user_id, book.id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    match (&member.role, &book.role) {
+        (BookRole::Owner, _) => {
+            return Err(RespErr::new(StatusCode::FORBIDDEN).user_msg("The owner cannot be removed"))
+        }
+        (BookRole::Admin, role) if *role != BookRole::Owner => {
+            return Err(
+                RespErr::new(StatusCode::FORBIDDEN).user_msg("Only the owner can remove an admin")
+            )
+        }
+        _ => {}
+    }
+
     remove_user_from_book(form.user_id, book.id, pool)
         .await
         .map_err(AppError::from)?;
@@ -111,6 +145,444 @@ pub async fn remove_user(
     Ok(())
 }
 
+#[derive(serde::Deserialize)]
+pub struct CapabilityForm {
+    user_id: i32,
+    capability: BookCapability,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ToggleParam {
+    toggle: bool,
+}
+
+pub async fn capability(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Query(ToggleParam { toggle }): Query<ToggleParam>,
+    Form(form): Form<CapabilityForm>,
+) -> Result<maud::Markup, RespErr> {
+    let pool = &state.pool;
+
+    let member = get_book(form.user_id, book_subscription.id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    let role = member.role.with_capability(form.capability, toggle).ok_or(
+        RespErr::new(StatusCode::BAD_REQUEST)
+            .user_msg("Only participants can be given admin capabilities"),
+    )?;
+
+    set_member_role(form.user_id, book_subscription.id, &role, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::book::admin::capability_toggle(
+        form.user_id,
+        form.capability,
+        toggle,
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct SpectatorForm {
+    user_id: i32,
+    username: String,
+}
+
+pub async fn spectator(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Query(ToggleParam { toggle }): Query<ToggleParam>,
+    Form(form): Form<SpectatorForm>,
+) -> Result<maud::Markup, RespErr> {
+    let pool = &state.pool;
+
+    let member = get_book(form.user_id, book_subscription.id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    let role = match (member.role, toggle) {
+        (BookRole::Participant | BookRole::Moderator { .. }, true) => BookRole::Spectator,
+        (BookRole::Spectator, false) => BookRole::Participant,
+        (role, _) => role,
+    };
+
+    set_member_role(form.user_id, book_subscription.id, &role, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::book::admin::member_row(
+        &BookMember {
+            id: form.user_id,
+            username: form.username,
+            role: sqlx::types::Json(role),
+        },
+        book_subscription.role.is_admin(),
+    ))
+}
+
+pub async fn managed_users(
+    auth_session: AuthSession,
+    Extension(book_subscription): Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &auth_session.backend.0;
+
+    let members = get_book_members(book_subscription.id, user.id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let managed = book_managed_users(book_subscription.id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let max_entries = get_max_entries(book_subscription.id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::book::admin::managed_section(
+        &user,
+        &members,
+        &managed,
+        max_entries,
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct MaxEntriesForm {
+    max_entries: i32,
+}
+
+pub async fn max_entries(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Form(form): Form<MaxEntriesForm>,
+) -> Result<AppNotification, AppNotification> {
+    if form.max_entries < 1 {
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Members need at least one entry".into(),
+        ));
+    }
+
+    set_max_entries(book_subscription.id, form.max_entries, &state.pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(AppNotification(
+        StatusCode::OK,
+        format!("Members may play up to {} entries", form.max_entries),
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct ManagedUserForm {
+    username: String,
+    owner_id: i32,
+}
+
+pub async fn add_managed_user(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Form(form): Form<ManagedUserForm>,
+) -> Result<maud::Markup, AppNotification> {
+    let pool = &state.pool;
+
+    let username = form.username.trim();
+    if username.is_empty() {
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Managed members need a name".into(),
+        ));
+    }
+
+    let managed = book_managed_users(book_subscription.id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let is_member = match get_book(form.owner_id, book_subscription.id, pool).await {
+        Ok(_) => true,
+        Err(sqlx::Error::RowNotFound) => false,
+        Err(err) => return Err(AppError::from(err).into()),
+    };
+    if !is_member || managed.iter().any(|managed| managed.id == form.owner_id) {
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Managed members must belong to a member of this book".into(),
+        ));
+    }
+
+    let managed = create_managed_user(username, form.owner_id, book_subscription.id, pool)
+        .await
+        .map_err(AppError::from)?
+        .ok_or(AppNotification(
+            StatusCode::CONFLICT,
+            "That name is already taken".into(),
+        ))?;
+
+    Ok(crate::view::book::admin::managed_row(&managed))
+}
+
+pub async fn missed_pick_policy(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let policy = book_policy(book_subscription.id, &state.pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::book::admin::missed_pick_policy_form(policy))
+}
+
+#[derive(serde::Deserialize)]
+pub struct MissedPickPolicyForm {
+    policy: MissedPickPolicy,
+}
+
+pub async fn set_missed_pick_policy(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Form(form): Form<MissedPickPolicyForm>,
+) -> Result<AppNotification, AppNotification> {
+    set_book_policy(book_subscription.id, form.policy, &state.pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(AppNotification(
+        StatusCode::OK,
+        format!("Missed picks: {}", form.policy.label()),
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct CreateInviteForm {
+    expires_in_days: String,
+    max_uses: String,
+}
+
+pub async fn create_book_invite(
+    auth_session: AuthSession,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Form(form): Form<CreateInviteForm>,
+) -> Result<maud::Markup, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &auth_session.backend.0;
+
+    let parse_optional = |value: &str, name: &str| {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        match value.parse::<i32>() {
+            Ok(amount) if amount > 0 => Ok(Some(amount)),
+            _ => Err(AppNotification(
+                StatusCode::BAD_REQUEST,
+                format!("{name} must be a positive number"),
+            )),
+        }
+    };
+
+    let expires_in_days = parse_optional(&form.expires_in_days, "Expiry")?;
+    let max_uses = parse_optional(&form.max_uses, "Max uses")?;
+
+    let invite = create_invite(
+        book_subscription.id,
+        user.id,
+        expires_in_days,
+        max_uses,
+        pool,
+    )
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(crate::view::book::admin::invite_row(&invite))
+}
+
+#[derive(serde::Deserialize)]
+pub struct DeleteInviteParams {
+    code: String,
+}
+
+pub async fn delete_book_invite(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Query(DeleteInviteParams { code }): Query<DeleteInviteParams>,
+) -> Result<(), AppError<'static>> {
+    delete_invite(&code, book_subscription.id, &state.pool).await?;
+
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+pub struct JoinRequestForm {
+    request_id: i32,
+    approve: bool,
+}
+
+pub async fn join_request(
+    auth_session: AuthSession,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Form(form): Form<JoinRequestForm>,
+) -> Result<(), AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &auth_session.backend.0;
+    let mut transaction = pool.begin().await.map_err(AppError::from)?;
+
+    let requester_id = resolve_join_request(
+        form.request_id,
+        book_subscription.id,
+        user.id,
+        form.approve,
+        &mut *transaction,
+    )
+    .await
+    .map_err(AppError::from)?
+    .ok_or(AppNotification(
+        StatusCode::NOT_FOUND,
+        "This request was already handled".into(),
+    ))?;
+
+    if form.approve {
+        add_user_to_book(requester_id, book_subscription.id, &mut *transaction)
+            .await
+            .map_err(AppError::from)?;
+    }
+
+    transaction.commit().await.map_err(AppError::from)?;
+
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+pub struct GroupMemberParams {
+    name: String,
+    user_id: i32,
+}
+
+pub async fn add_to_group(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Form(params): Form<GroupMemberParams>,
+) -> Result<maud::Markup, AppNotification> {
+    let pool = &state.pool;
+
+    let name = params.name.trim();
+    if name.is_empty() || name.len() > 30 {
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Group name must be 1-30 characters".into(),
+        ));
+    }
+
+    add_group_member(book_subscription.id, name, params.user_id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    let groups = get_book_groups(book_subscription.id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::book::admin::group_list(&groups))
+}
+
+pub async fn remove_from_group(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Query(params): Query<GroupMemberParams>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    remove_group_member(book_subscription.id, &params.name, params.user_id, pool).await?;
+
+    let groups = get_book_groups(book_subscription.id, pool).await?;
+
+    Ok(crate::view::book::admin::group_list(&groups))
+}
+
+pub async fn add_points_form(
+    auth_session: AuthSession,
+    Extension(book_subscription): Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &auth_session.backend.0;
+
+    let members = get_book_members(book_subscription.id, user.id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let chapters = get_chapters(book_subscription.id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::book::admin::add_points_form(
+        &user, &members, &chapters,
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct AddPointsForm {
+    user_id: i32,
+    points: i32,
+    reason: String,
+    chapter_id: Option<String>,
+}
+
+pub async fn add_points(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Form(form): Form<AddPointsForm>,
+) -> Result<AppNotification, AppNotification> {
+    let pool = &state.pool;
+
+    if form.reason.trim().is_empty() {
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Please give a reason for the points".into(),
+        ));
+    }
+
+    // Spectators don't play, so they have no standing to add points to
+    match get_book(form.user_id, book_subscription.id, pool).await {
+        Ok(member) if !matches!(member.role, BookRole::Spectator) => {}
+        Ok(_) | Err(sqlx::Error::RowNotFound) => {
+            return Err(AppNotification(
+                StatusCode::NOT_FOUND,
+                "That member isn't playing in this book".into(),
+            ))
+        }
+        Err(err) => return Err(AppError::from(err).into()),
+    }
+
+    let chapter_id = match form.chapter_id.filter(|id| !id.is_empty()) {
+        Some(id) => {
+            let chapter_id = id
+                .parse::<i32>()
+                .map_err(|_| AppError::Parse("Invalid chapter"))?;
+            let chapters = get_chapters(book_subscription.id, pool)
+                .await
+                .map_err(AppError::from)?;
+            if !chapters
+                .iter()
+                .any(|chapter| chapter.chapter_id == chapter_id)
+            {
+                return Err(AppError::Parse("Invalid chapter").into());
+            }
+            Some(chapter_id)
+        }
+        None => None,
+    };
+
+    add_book_points(
+        form.user_id,
+        book_subscription.id,
+        form.points,
+        form.reason.trim(),
+        chapter_id,
+        pool,
+    )
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(AppNotification(StatusCode::OK, "Points Added".into()))
+}
+
 pub async fn delete(
     State(state): State<AppStateRef>,
     Extension(book_subscription): Extension<BookSubscription>,
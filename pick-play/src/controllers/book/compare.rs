@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Query, State},
+    Extension,
+};
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        book::{get_book_users, BookRole, BookSubscription},
+        chapter::get_chapters,
+        compare::head_to_head,
+        event::{get_closed_book_events, get_closed_book_picks},
+        team::get_book_teams,
+    },
+    AppError, AppStateRef,
+};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CompareParams {
+    first: Option<String>,
+    second: Option<String>,
+}
+
+pub async fn handler(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Query(params): Query<CompareParams>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let members = get_book_users(book_subscription.id, pool).await?;
+    let member = |id: Option<String>| {
+        let id = id?.parse::<i32>().ok()?;
+        members.iter().find(|(user_id, _)| *user_id == id).cloned()
+    };
+
+    let first = member(params.first).or_else(|| {
+        members
+            .iter()
+            .find(|(user_id, _)| *user_id == user.id)
+            .cloned()
+    });
+    let second = member(params.second);
+
+    let comparison = match (&first, &second) {
+        (Some((first_id, _)), Some((second_id, _))) if first_id != second_id => {
+            // Guests only see the chapters they were invited to
+            let chapter_ids = match &book_subscription.role {
+                BookRole::Guest { chapter_ids } => Some(chapter_ids.as_slice()),
+                _ => None,
+            };
+            let events = get_closed_book_events(book_subscription.id, chapter_ids, pool).await?;
+            let picks = get_closed_book_picks(
+                book_subscription.id,
+                chapter_ids,
+                &[*first_id, *second_id],
+                pool,
+            )
+            .await?;
+
+            Some(head_to_head(*first_id, *second_id, &events, &picks))
+        }
+        _ => None,
+    };
+
+    let chapters = get_chapters(book_subscription.id, pool)
+        .await?
+        .into_iter()
+        .map(|chapter| (chapter.chapter_id, chapter.title))
+        .collect();
+    let teams = get_book_teams(book_subscription.id, pool).await?;
+
+    Ok(crate::view::book::compare::m(
+        &user.username,
+        &book_subscription,
+        &members,
+        [first.as_ref(), second.as_ref()],
+        comparison.as_ref(),
+        &chapters,
+        &teams,
+    ))
+}
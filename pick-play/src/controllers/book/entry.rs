@@ -0,0 +1,63 @@
+use axum::{extract::State, response::IntoResponse, Extension, Form};
+use reqwest::StatusCode;
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        book::{BookRole, BookSubscription},
+        entry::{create_entry, owned_entries},
+    },
+    AppError, AppNotification, AppStateRef,
+};
+
+#[derive(serde::Deserialize)]
+pub struct EntryForm {
+    name: String,
+}
+
+pub async fn add_entry(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Form(form): Form<EntryForm>,
+) -> Result<impl IntoResponse, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+
+    if matches!(
+        book_subscription.role,
+        BookRole::Guest { .. } | BookRole::Spectator
+    ) {
+        return Err(AppNotification(
+            StatusCode::FORBIDDEN,
+            "Only members can open extra entries".into(),
+        ));
+    }
+
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Entries need a name".into(),
+        ));
+    }
+
+    let entries = owned_entries(user.id, book_subscription.id, &state.pool)
+        .await
+        .map_err(AppError::from)?;
+    if !entries.can_add {
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "This book doesn't allow any more entries".into(),
+        ));
+    }
+
+    create_entry(user.id, name, book_subscription.id, &state.pool)
+        .await
+        .map_err(AppError::from)?
+        .ok_or(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "You already have an entry with that name".into(),
+        ))?;
+
+    Ok([("HX-Refresh", "true")])
+}
@@ -1,15 +1,18 @@
 use axum::{
+    handler::Handler as _,
     middleware,
     routing::{get, post},
     Router,
 };
 
-use crate::{auth::authz, AppStateRef};
+use crate::{auth::authz, model::book::BookCapability, AppStateRef};
 
-use super::chapter;
+use super::{chapter, team};
 
 pub mod admin;
+pub mod compare;
 pub mod create;
+pub mod entry;
 pub mod page;
 
 #[inline]
@@ -21,14 +24,82 @@ pub fn router() -> Router<AppStateRef> {
                 .nest(
                     "/admin/",
                     Router::new()
-                        .route("/", get(admin::handler).delete(admin::delete))
-                        .route("/user-search", get(admin::search_user))
-                        .route("/add-user", post(admin::add_user))
-                        .route("/remove-user", post(admin::remove_user)),
+                        .route(
+                            "/",
+                            get(admin::handler).delete(
+                                admin::delete.layer(middleware::from_fn(mw::require_admin)),
+                            ),
+                        )
+                        .route(
+                            "/capability",
+                            post(admin::capability.layer(middleware::from_fn(mw::require_admin))),
+                        )
+                        .merge(
+                            Router::new()
+                                .route("/user-search", get(admin::search_user))
+                                .route("/add-user", post(admin::add_user))
+                                .route("/remove-user", post(admin::remove_user))
+                                .route("/spectator", post(admin::spectator))
+                                .route("/join-request", post(admin::join_request))
+                                .route(
+                                    "/managed-users",
+                                    get(admin::managed_users).post(admin::add_managed_user),
+                                )
+                                .route("/max-entries", post(admin::max_entries))
+                                .route(
+                                    "/invite",
+                                    post(admin::create_book_invite)
+                                        .delete(admin::delete_book_invite),
+                                )
+                                .route(
+                                    "/group",
+                                    post(admin::add_to_group).delete(admin::remove_from_group),
+                                )
+                                .route_layer(middleware::from_fn_with_state(
+                                    BookCapability::ManageMembers,
+                                    mw::require_capability,
+                                )),
+                        )
+                        .merge(
+                            Router::new()
+                                .route(
+                                    "/missed-pick-policy",
+                                    get(admin::missed_pick_policy)
+                                        .post(admin::set_missed_pick_policy),
+                                )
+                                .route_layer(middleware::from_fn_with_state(
+                                    BookCapability::ManageChapters,
+                                    mw::require_capability,
+                                )),
+                        )
+                        .merge(
+                            Router::new()
+                                .route(
+                                    "/add-points",
+                                    get(admin::add_points_form).post(admin::add_points),
+                                )
+                                .route_layer(middleware::from_fn_with_state(
+                                    BookCapability::AdjustPoints,
+                                    mw::require_capability,
+                                )),
+                        ),
                 )
-                .route_layer(middleware::from_fn(mw::require_admin))
+                .route_layer(middleware::from_fn(mw::require_any_capability))
                 .nest("/chapter/", chapter::router())
                 .route("/leaderboard", get(page::leaderboard))
+                .route("/leaderboard/groups", get(page::group_standings))
+                .route("/stats", get(page::player_stats))
+                .route("/race", get(page::race))
+                .route("/recaps", get(page::recaps))
+                .route("/trophies", get(page::trophies))
+                .merge(
+                    Router::new()
+                        .route("/compare", get(compare::handler))
+                        .route("/consensus", get(page::consensus))
+                        .route("/team/{team_id}", get(team::page::handler))
+                        .route_layer(middleware::from_fn(mw::hide_while_extended)),
+                )
+                .route("/entries", post(entry::add_entry))
                 .route("/", get(page::book_page)),
         )
         .route_layer(middleware::from_fn(mw::require_member))
@@ -41,7 +112,7 @@ pub fn router() -> Router<AppStateRef> {
 pub mod mw {
     use axum::{
         body::Body,
-        extract::{Path, Request},
+        extract::{Path, Request, State},
         http::{Response, StatusCode},
         middleware::Next,
         response::{ErrorResponse, IntoResponse, Redirect},
@@ -51,7 +122,10 @@ pub mod mw {
 
     use crate::{
         auth::{AuthSession, BackendPgDB},
-        model::book::{get_book, BookRole, BookSubscription},
+        model::{
+            book::{get_book, BookCapability, BookRole, BookSubscription},
+            extension::active_extension_chapter,
+        },
         AppError,
     };
 
@@ -93,7 +167,21 @@ pub mod mw {
         request: Request,
         next: Next,
     ) -> Result<Response<Body>, RespErr> {
-        if book_subscription.role != BookRole::Admin {
+        if !book_subscription.role.is_admin() {
+            return Err(
+                AppError::Unauthorized("You do not have admin privilages for this book").into(),
+            );
+        }
+
+        Ok(next.run(request).await)
+    }
+
+    pub async fn require_any_capability(
+        Extension(book_subscription): Extension<BookSubscription>,
+        request: Request,
+        next: Next,
+    ) -> Result<Response<Body>, RespErr> {
+        if !book_subscription.role.has_any_capability() {
             return Err(
                 AppError::Unauthorized("You do not have admin privilages for this book").into(),
             );
@@ -101,4 +189,45 @@ pub mod mw {
 
         Ok(next.run(request).await)
     }
+
+    pub async fn require_capability(
+        State(capability): State<BookCapability>,
+        Extension(book_subscription): Extension<BookSubscription>,
+        request: Request,
+        next: Next,
+    ) -> Result<Response<Body>, RespErr> {
+        if !book_subscription.role.has_capability(capability) {
+            return Err(AppError::Unauthorized(
+                "You do not have permission to do that in this book",
+            )
+            .into());
+        }
+
+        Ok(next.run(request).await)
+    }
+
+    /// Book pages built from every closed chapter's picks stay hidden while
+    /// the member is still submitting one of them under an extension
+    pub async fn hide_while_extended(
+        auth_session: AuthSession,
+        Extension(book_subscription): Extension<BookSubscription>,
+        request: Request,
+        next: Next,
+    ) -> Result<Response<Body>, ErrorResponse> {
+        let user = auth_session.user.ok_or(AppError::BackendUser)?;
+        let BackendPgDB(pool) = auth_session.backend;
+
+        if let Some(chapter_id) = active_extension_chapter(book_subscription.id, user.id, &pool)
+            .await
+            .map_err(AppError::from)?
+        {
+            return Err(Redirect::to(&format!(
+                "/book/{}/chapter/{chapter_id}/",
+                book_subscription.id
+            ))
+            .into());
+        }
+
+        Ok(next.run(request).await)
+    }
 }
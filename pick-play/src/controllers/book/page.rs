@@ -1,10 +1,18 @@
-use axum::{extract::State, Extension};
+use axum::{
+    extract::{Query, State},
+    Extension,
+};
 
 use crate::{
     auth::{AuthSession, BackendPgDB},
     model::{
-        book::{BookRole, BookSubscription},
-        chapter::chapters_with_stats,
+        achievement::{badge_counts, book_achievements},
+        book::{standings_history, BookRole, BookSubscription},
+        chapter::{chapters_with_stats, graded_chapters, GradedChapter},
+        consensus::season_record,
+        group::{get_book_groups, group_leaderboard, BookGroup},
+        player_ranking::book_player_rankings,
+        recap::book_recaps,
     },
     AppError, AppStateRef,
 };
@@ -31,15 +39,42 @@ pub async fn book_page(
     ))
 }
 
+#[derive(Debug, serde::Deserialize)]
+pub struct LeaderboardParams {
+    group: Option<String>,
+    as_of: Option<String>,
+}
+
 pub async fn leaderboard(
     State(state): State<AppStateRef>,
     book_subscription: Extension<BookSubscription>,
+    Query(LeaderboardParams { group, as_of }): Query<LeaderboardParams>,
 ) -> Result<maud::Markup, AppError<'static>> {
     let pool = &state.pool;
 
-    let rankings = crate::model::book::leaderboard(book_subscription.id, pool).await?;
+    let group = group.filter(|group| !group.is_empty());
+    let as_of = as_of.and_then(|as_of| as_of.parse().ok());
+    let rankings =
+        crate::model::book::leaderboard(book_subscription.id, group.as_deref(), as_of, pool)
+            .await?;
+    let groups = get_book_groups(book_subscription.id, pool).await?;
+    let chapters = graded_chapters(book_subscription.id, pool).await?;
+    let recaps = book_recaps(book_subscription.id, pool).await?;
+    let badges = badge_counts(&book_achievements(book_subscription.id, pool).await?);
+    // Link the recap of the chapter being viewed, or the latest one
+    let recap = match as_of {
+        Some(as_of) => recaps.iter().find(|recap| recap.chapter_id == as_of),
+        None => recaps.first(),
+    };
 
     Ok(maud::html! {
+        div id="leaderboard" {
+        (leaderboard_filter(&groups, &chapters, group.as_deref(), as_of))
+        @if let Some(recap) = recap {
+            p class="text-sm text-center" {
+                a href={"chapter/"(recap.chapter_id)"/recap"} class="text-blue-400 hover:underline" { (recap.title) " Recap" }
+            }
+        }
         div class="flex justify-center w-full" {
             table class="w-auto max-w-md text-sm" {
                 thead class="text-xs text-gray-700 uppercase bg-green-400" {
@@ -59,7 +94,14 @@ pub async fn leaderboard(
                                 td {}
                             }
                             td class="px-6 py-4" {
-                                (rank.username)
+                                @if rank.user_id > 0 {
+                                    a href={"/user/"(rank.user_id)} class="hover:underline" { (rank.username) }
+                                    @if let Some(badges) = badges.get(&rank.user_id) {
+                                        (crate::view::achievement::badges(badges))
+                                    }
+                                } @else {
+                                    (rank.username)
+                                }
                                 br;
                                 @if rank.added_points > 0 {
                                     span class="text-red-500" {"Added Points: "(rank.added_points)}
@@ -71,5 +113,197 @@ pub async fn leaderboard(
                 }
             }
         }
+        }
+    })
+}
+
+fn leaderboard_filter(
+    groups: &[BookGroup],
+    chapters: &[GradedChapter],
+    selected_group: Option<&str>,
+    as_of: Option<i32>,
+) -> maud::Markup {
+    let position = as_of.and_then(|as_of| chapters.iter().position(|chapter| chapter.id == as_of));
+    // Stepping back from the live standings starts at the latest graded chapter
+    let previous = match position {
+        Some(position) => position.checked_sub(1).and_then(|i| chapters.get(i)),
+        None => chapters.len().checked_sub(2).and_then(|i| chapters.get(i)),
+    };
+    let next = position.and_then(|position| chapters.get(position + 1));
+
+    maud::html! {
+        form
+            hx-get="leaderboard"
+            hx-trigger="change"
+            hx-target="#leaderboard"
+            hx-swap="outerHTML"
+            class="flex flex-wrap items-center justify-center gap-2 p-2" {
+            @if !groups.is_empty() {
+                select name="group" class="border border-green-300 rounded" {
+                    option value="" selected[selected_group.is_none()] { "Everyone" }
+                    @for group in groups {
+                        option value=(group.name) selected[selected_group == Some(group.name.as_str())] { (group.name) }
+                    }
+                }
+            }
+            @if !chapters.is_empty() {
+                button
+                    type="button"
+                    hx-vals={r#"{"as_of":""#(previous.map(|chapter| chapter.id.to_string()).unwrap_or_default())r#""}"#}
+                    disabled[previous.is_none()]
+                    hx-get="leaderboard"
+                    hx-include="closest form"
+                    hx-target="#leaderboard"
+                    hx-swap="outerHTML"
+                    class="px-2 text-green-700 disabled:text-gray-300" {
+                    "◀"
+                }
+                select name="as_of" class="border border-green-300 rounded" {
+                    option value="" selected[position.is_none()] { "Current" }
+                    @for chapter in chapters.iter().rev() {
+                        option value=(chapter.id) selected[as_of == Some(chapter.id)] { "After " (chapter.title) }
+                    }
+                }
+                button
+                    type="button"
+                    hx-vals={r#"{"as_of":""#(next.map(|chapter| chapter.id.to_string()).unwrap_or_default())r#""}"#}
+                    disabled[position.is_none()]
+                    hx-get="leaderboard"
+                    hx-include="closest form"
+                    hx-target="#leaderboard"
+                    hx-swap="outerHTML"
+                    class="px-2 text-green-700 disabled:text-gray-300" {
+                    "▶"
+                }
+            }
+            @if !groups.is_empty() {
+                button
+                    type="button"
+                    hx-get="leaderboard/groups"
+                    hx-target="#leaderboard"
+                    hx-swap="outerHTML"
+                    class="px-2 py-1 text-sm font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                    "Group vs Group"
+                }
+            }
+        }
+    }
+}
+
+pub async fn recaps(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let recaps = book_recaps(book_subscription.id, &state.pool).await?;
+    let recaps = recaps
+        .iter()
+        .filter(|recap| match &book_subscription.role {
+            BookRole::Guest { chapter_ids } => chapter_ids.contains(&recap.chapter_id),
+            _ => true,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(maud::html! {
+        ul class="w-full p-3 mt-2 bg-white border border-gray-300 rounded-lg shadow-lg" {
+            @if recaps.is_empty() {
+                li class="text-gray-500" { "No recaps yet" }
+            }
+            @for recap in recaps {
+                li {
+                    a href={"chapter/"(recap.chapter_id)"/recap"} class="text-blue-400 hover:underline" { (recap.title) }
+                }
+            }
+        }
+    })
+}
+
+pub async fn trophies(
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let achievements = book_achievements(book_subscription.id, &state.pool).await?;
+
+    Ok(crate::view::achievement::trophy_case(&achievements))
+}
+
+pub async fn group_standings(
+    State(state): State<AppStateRef>,
+    book_subscription: Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    let standings = group_leaderboard(book_subscription.id, pool).await?;
+    let groups = get_book_groups(book_subscription.id, pool).await?;
+
+    Ok(maud::html! {
+        div id="leaderboard" {
+            (leaderboard_filter(&groups, &[], None, None))
+            div class="flex justify-center w-full" {
+                table class="w-auto max-w-md text-sm" {
+                    thead class="text-xs text-gray-700 uppercase bg-green-400" {
+                        tr {
+                            th scope="col" class="px-6 py-3" { "Rank" }
+                            th scope="col" class="px-6 py-3" { "Group" }
+                            th scope="col" class="px-6 py-3" { "Average" }
+                            th scope="col" class="px-6 py-3" { "Total" }
+                        }
+                    }
+                    tbody {
+                        @for (i, standing) in standings.iter().enumerate() {
+                            tr class="bg-white" {
+                                @if standing.rank == i as i32 + 1 {
+                                    td class="px-6 py-4" {(i + 1)}
+                                } @else {
+                                    td {}
+                                }
+                                td class="px-6 py-4" {
+                                    (standing.name)
+                                    br;
+                                    span class="text-xs text-gray-500" { (standing.num_members) " Members" }
+                                }
+                                td class="px-6 py-4" {(format!("{:.1}", standing.average_points))}
+                                td class="px-6 py-4" {(standing.total_points)}
+                            }
+                        }
+                    }
+                }
+            }
+        }
     })
 }
+
+pub async fn race(
+    auth_session: AuthSession,
+    Extension(book_subscription): Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let BackendPgDB(pool) = auth_session.backend;
+
+    let history = standings_history(book_subscription.id, &pool).await?;
+
+    Ok(crate::view::book::race::m(
+        &user,
+        &book_subscription,
+        &history,
+    ))
+}
+
+pub async fn player_stats(
+    State(state): State<AppStateRef>,
+    book_subscription: Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let rankings = book_player_rankings(book_subscription.id, &state.pool).await?;
+
+    Ok(crate::view::player_rankings::player_rankings_card(
+        &rankings,
+    ))
+}
+
+pub async fn consensus(
+    State(state): State<AppStateRef>,
+    book_subscription: Extension<BookSubscription>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let record = season_record(book_subscription.id, &state.pool).await?;
+
+    Ok(crate::view::chapter::closed::season_consensus(&record))
+}
@@ -1,18 +1,25 @@
-use std::{borrow::BorrowMut, collections::HashMap};
+use std::collections::HashMap;
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
-    Extension, Json,
+    Extension, Form, Json,
 };
 use axum_ctx::{RespErr, RespErrCtx, RespErrExt, StatusCode};
+use sqlx::PgPool;
 
 use crate::{
     auth::{AuthSession, BackendPgDB},
     model::{
+        achievement::evaluate_achievements,
         book::BookSubscription,
-        chapter::Chapter,
+        chapter::{chapter_order, get_chapter_members, move_chapter, Chapter},
+        commitment::commit_picks,
         event::{get_events, EventContent},
+        extension::{chapter_extensions, grant_extension, revoke_extension},
+        grading::{apply_grades, grading_history, revert_to_before, EventChange},
+        missed_pick::{apply_missed_pick_policy, apply_missed_scores},
+        recap::generate_recap,
         team::get_chapter_teams,
     },
     AppError, AppNotification, AppStateRef,
@@ -37,6 +44,7 @@ pub async fn get(
     Ok(crate::view::chapter::admin::m(
         &user.username,
         &book_subscription.name,
+        &book_subscription.role,
         chapter,
         events,
         relevent_teams,
@@ -67,13 +75,38 @@ pub enum AnswerEventContent {
     UserInput { choices: Option<Vec<String>> },
 }
 
+/// Selection for a game that has not been played yet
+const PENDING: &str = "pending";
+
+/// Refreshes everything derived from a chapter's grades
+pub(super) async fn after_grading(chapter: &Chapter, pool: &PgPool) {
+    if let Err(err) = apply_missed_scores(chapter.chapter_id, chapter.book_id, pool).await {
+        tracing::error!(
+            "Could not score missed picks for chapter {}: {err}",
+            chapter.chapter_id
+        );
+    } else if let Err(err) = generate_recap(chapter.chapter_id, chapter.book_id, pool).await {
+        tracing::error!(
+            "Could not generate recap for chapter {}: {err}",
+            chapter.chapter_id
+        );
+    } else if let Err(err) = evaluate_achievements(chapter.book_id, pool).await {
+        tracing::error!(
+            "Could not evaluate achievements for book {}: {err}",
+            chapter.book_id
+        );
+    }
+}
+
 pub async fn post(
+    auth_session: AuthSession,
     State(state): State<AppStateRef>,
     Extension(chapter): Extension<Chapter>,
     Json(AnswerSubmission {
         events: event_submissions,
     }): Json<AnswerSubmission>,
 ) -> Result<AppNotification, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
     let pool = &state.pool;
 
     let events = get_events(chapter.chapter_id, pool)
@@ -87,17 +120,19 @@ pub async fn post(
         .ctx(StatusCode::BAD_REQUEST)
         .user_msg("Could not parse event id")?;
 
-    let (ids, contents) = events
-        .clone()
+    // Events left out of the submission keep their current answers
+    let changes = events
         .into_iter()
-        .map(|mut event| {
-            let submission = event_submissions.remove(&event.id).ok_or(
-                RespErr::new(StatusCode::BAD_REQUEST)
-                    .user_msg("Not all events were included in request"),
-            )?;
-            match (event.contents.0.borrow_mut(), submission) {
+        .filter_map(|event| {
+            let submission = event_submissions.remove(&event.id)?;
+            Some((event, submission))
+        })
+        .map(|(event, submission)| {
+            let before = event.contents.0;
+            let mut after = before.clone();
+            match (&mut after, submission) {
                 (
-                    EventContent::SpreadGroup(ref mut spreads),
+                    EventContent::SpreadGroup(spreads),
                     AnswerEventContent::SpreadGroup { selections },
                 ) => {
                     if spreads.len() != selections.len() {
@@ -105,119 +140,45 @@ pub async fn post(
                             .user_msg("Spread Group Submissions do not cover all games"));
                     }
 
-                    spreads
-                        .iter_mut()
-                        .zip(selections.into_iter())
-                        .for_each(|(spread, selection)| spread.answer = Some(selection));
-
-                    Ok((
-                        event.id,
-                        serde_json::to_value(event.contents)
-                            .expect("Failed to serialize spread group event contents to JSON"),
-                    ))
+                    // Games that have not been played yet stay ungraded
+                    spreads.iter_mut().zip(selections.into_iter()).for_each(
+                        |(spread, selection)| {
+                            spread.answer = (selection != PENDING).then_some(selection)
+                        },
+                    );
                 }
                 (EventContent::UserInput(input), AnswerEventContent::UserInput { choices }) => {
-                    input.acceptable_answers =
-                        Some(choices.unwrap_or_default().into_iter().collect());
-                    Ok((
-                        event.id,
-                        serde_json::to_value(event.contents)
-                            .expect("Failed to serialize user input event contents to JSON"),
-                    ))
+                    // A question without any accepted answer is still pending
+                    input.acceptable_answers = choices
+                        .filter(|choices| !choices.is_empty())
+                        .map(|choices| choices.into_iter().collect());
+                }
+                _ => {
+                    return Err(RespErr::new(StatusCode::BAD_REQUEST)
+                        .user_msg("Submitted event does not match its actual type"))
                 }
-                _ => Err(RespErr::new(StatusCode::BAD_REQUEST)
-                    .user_msg("Submitted event does not match its actual type")),
             }
+            Ok(EventChange {
+                event_id: event.id,
+                before,
+                after,
+            })
         })
-        .try_fold(
-            (Vec::new(), Vec::new()),
-            |(mut ids, mut contents), curr_item| {
-                let (curr_id, curr_contents) = curr_item?;
-                ids.push(curr_id);
-                contents.push(curr_contents);
-                Ok::<_, RespErr>((ids, contents))
-            },
-        )?;
-
-    let mut transaction = pool.begin().await.map_err(AppError::from)?;
+        // Only events whose answers changed need their picks regraded
+        .filter(|change| {
+            !matches!(change, Ok(change)
+                if serde_json::to_value(&change.before).ok() == serde_json::to_value(&change.after).ok())
+        })
+        .collect::<Result<Vec<_>, RespErr>>()?;
 
-    sqlx::query!(
-        r#"
-        UPDATE events AS e
-        SET contents = c.contents
-        FROM UNNEST($1::INT[], $2::JSONB[]) AS c(id, contents)
-        WHERE e.id = c.id
-        "#,
-        &ids,
-        contents.as_slice()
-    )
-    .execute(&mut *transaction)
-    .await
-    .map_err(AppError::from)?;
+    if changes.is_empty() {
+        return Ok(AppNotification(StatusCode::OK, "No answers changed".into()));
+    }
 
-    sqlx::query!(
-        "
-        UPDATE PICKS
-        SET
-            POINTS = CALCULATIONS.POINTS_AWARDED
-        FROM
-            (
-                SELECT
-                    EVENT_ID,
-                    USER_ID,
-                    SUM(POINTS_AWARDED) AS POINTS_AWARDED
-                FROM
-                    (
-                        SELECT
-                            EVENT_ID,
-                            USER_ID,
-                            CASE
-                                WHEN CORRECT THEN WAGER
-                                ELSE 0
-                            END AS POINTS_AWARDED
-                        FROM
-                            (
-                                SELECT
-                                    E.ID AS EVENT_ID,
-                                    P.USER_ID,
-                                    JSONB_ARRAY_ELEMENTS(P.WAGER)::INT AS WAGER,
-                                    JSONB_ARRAY_ELEMENTS(E.CONTENTS -> 'spread_group') ->> 'answer' = JSONB_ARRAY_ELEMENTS(P.CHOICE) #>> '{}' AS CORRECT
-                                FROM
-                                    EVENTS AS E
-                                    JOIN PICKS AS P ON E.ID = P.EVENT_ID
-                                WHERE
-                                    E.EVENT_TYPE = 'spread_group'
-                                    AND E.CHAPTER_ID = $1
-                            ) as t2
-                    ) as t1
-                GROUP BY
-                    EVENT_ID,
-                    USER_ID
-                UNION
-                SELECT
-                    E.ID AS EVENT_ID,
-                    P.USER_ID,
-                    CASE
-                        WHEN E.CONTENTS -> 'user_input' -> 'acceptable_answers' @> P.CHOICE THEN P.WAGER::INTEGER
-                        ELSE 0
-                    END AS POINTS_AWARDED
-                FROM
-                    EVENTS AS E
-                    JOIN PICKS AS P ON E.ID = P.EVENT_ID
-                WHERE
-                    E.EVENT_TYPE = 'user_input'
-                    AND E.CHAPTER_ID = $1
-            ) AS CALCULATIONS
-        WHERE
-            PICKS.EVENT_ID = CALCULATIONS.EVENT_ID
-            AND PICKS.USER_ID = CALCULATIONS.USER_ID
-        ",
-        chapter.chapter_id
-    ).execute(&mut *transaction)
-    .await
-    .map_err(AppError::from)?;
-
-    transaction.commit().await.map_err(AppError::from)?;
+    apply_grades(&chapter, user.id, changes, None, pool)
+        .await
+        .map_err(AppError::from)?;
+    after_grading(&chapter, pool).await;
 
     Ok(AppNotification(StatusCode::OK, "Answers Saved".into()))
 }
@@ -232,7 +193,7 @@ pub async fn open(
     Extension(chapter): Extension<Chapter>,
     Query(ToggleParam { toggle }): Query<ToggleParam>,
 ) -> Result<maud::Markup, AppError<'static>> {
-    let pool = &state.pool;
+    let mut transaction = state.pool.begin().await?;
 
     sqlx::query!(
         r#"
@@ -243,9 +204,23 @@ pub async fn open(
         toggle,
         chapter.chapter_id
     )
-    .execute(pool)
+    .execute(&mut *transaction)
     .await?;
 
+    // Locking fills in missed picks, then publishes a commitment to
+    // everyone's picks
+    if !toggle {
+        apply_missed_pick_policy(chapter.chapter_id, chapter.book_id, &mut transaction).await?;
+        commit_picks(chapter.chapter_id, None, &mut transaction).await?;
+    }
+
+    transaction.commit().await?;
+
+    // Relocking a graded chapter changes who is owed a score
+    if !toggle {
+        apply_missed_scores(chapter.chapter_id, chapter.book_id, &state.pool).await?;
+    }
+
     Ok(crate::view::chapter::admin::chapter_open_button(toggle))
 }
 
@@ -271,6 +246,37 @@ pub async fn visible(
     Ok(crate::view::chapter::admin::chapter_visible_button(toggle))
 }
 
+pub async fn order(
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let (place, total) = chapter_order(chapter.chapter_id, chapter.book_id, &state.pool).await?;
+
+    Ok(crate::view::chapter::admin::chapter_order_buttons(
+        place, total,
+    ))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MoveParams {
+    earlier: bool,
+}
+
+pub async fn reorder(
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Query(MoveParams { earlier }): Query<MoveParams>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    move_chapter(chapter.chapter_id, chapter.book_id, earlier, pool).await?;
+    let (place, total) = chapter_order(chapter.chapter_id, chapter.book_id, pool).await?;
+
+    Ok(crate::view::chapter::admin::chapter_order_buttons(
+        place, total,
+    ))
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub struct UserInputParams {
     #[serde(rename(deserialize = "event-id"))]
@@ -317,6 +323,26 @@ pub async fn delete(
 ) -> Result<impl IntoResponse, AppError<'static>> {
     let mut transaction = state.pool.begin().await?;
 
+    sqlx::query!(
+        "
+        DELETE FROM pick_drafts
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM pick_revisions
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
     sqlx::query!(
         "
         DELETE FROM picks
@@ -337,6 +363,105 @@ pub async fn delete(
     .execute(&mut *transaction)
     .await?;
 
+    sqlx::query!(
+        "
+        DELETE FROM pick_commitments
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM chapter_commitments
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM grading_changes
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM chapter_recaps
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM achievements
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM deadline_extensions
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM missed_picks
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM added_points
+        WHERE chapter_id = $1 AND auto_policy IS NOT NULL
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    // Manual points move back to the previous chapter so snapshots keep them
+    sqlx::query!(
+        "
+        UPDATE added_points
+        SET chapter_id = (
+            SELECT prev.id
+            FROM chapters AS prev
+            JOIN chapters AS deleted ON prev.book_id = deleted.book_id
+            WHERE deleted.id = $1 AND prev.position < deleted.position
+            ORDER BY prev.position DESC
+            LIMIT 1
+        )
+        WHERE chapter_id = $1
+        ",
+        chapter.chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
     sqlx::query!(
         "
         DELETE FROM chapters
@@ -349,9 +474,146 @@ pub async fn delete(
 
     transaction.commit().await?;
 
+    // Streaks may have run through the deleted chapter
+    if let Err(err) = evaluate_achievements(chapter.book_id, &state.pool).await {
+        tracing::error!(
+            "Could not evaluate achievements for book {}: {err}",
+            chapter.book_id
+        );
+    }
+
     Ok([("HX-Redirect", "../../..")].into_response())
 }
 
+pub async fn history(
+    Extension(chapter): Extension<Chapter>,
+    State(state): State<AppStateRef>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    let history = grading_history(chapter.chapter_id, pool).await?;
+    let teams = get_chapter_teams(chapter.chapter_id, pool).await?;
+
+    Ok(crate::view::chapter::admin::grading_history(
+        &history, &teams,
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct GradingChangePath {
+    book_id: i32,
+    chapter_id: i32,
+    change_id: i32,
+}
+
+pub async fn revert(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Path(GradingChangePath {
+        book_id: _b,
+        chapter_id: _c,
+        change_id,
+    }): Path<GradingChangePath>,
+) -> Result<impl IntoResponse, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let history = grading_history(chapter.chapter_id, pool)
+        .await
+        .map_err(AppError::from)?;
+    if !history.iter().any(|change| change.id == change_id) {
+        return Err(AppNotification(
+            StatusCode::NOT_FOUND,
+            "Grading change not found".into(),
+        ));
+    }
+
+    let events = get_events(chapter.chapter_id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let changes = revert_to_before(change_id, &history, &events);
+
+    apply_grades(&chapter, user.id, changes, Some(change_id), pool)
+        .await
+        .map_err(AppError::from)?;
+    after_grading(&chapter, pool).await;
+
+    Ok([("HX-Refresh", "true")].into_response())
+}
+
+pub async fn extensions(
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    let users = get_chapter_members(chapter.book_id, chapter.chapter_id, pool).await?;
+    let extensions = chapter_extensions(chapter.chapter_id, pool).await?;
+
+    Ok(crate::view::chapter::admin::extensions(&users, &extensions))
+}
+
+#[derive(serde::Deserialize)]
+pub struct ExtensionForm {
+    user_id: i32,
+    hours: i32,
+}
+
+pub async fn grant(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Form(form): Form<ExtensionForm>,
+) -> Result<maud::Markup, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    if form.hours < 1 {
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Extensions must last at least an hour".into(),
+        ));
+    }
+
+    let users = get_chapter_members(chapter.book_id, chapter.chapter_id, pool).await?;
+    if !users.iter().any(|member| member.user_id == form.user_id) {
+        return Err(AppNotification(
+            StatusCode::NOT_FOUND,
+            "That member can't make picks in this chapter".into(),
+        ));
+    }
+
+    grant_extension(chapter.chapter_id, form.user_id, form.hours, user.id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let extensions = chapter_extensions(chapter.chapter_id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::chapter::admin::extensions(&users, &extensions))
+}
+
+#[derive(serde::Deserialize)]
+pub struct RevokeExtensionParams {
+    user_id: i32,
+}
+
+pub async fn revoke(
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Query(RevokeExtensionParams { user_id }): Query<RevokeExtensionParams>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    revoke_extension(chapter.chapter_id, user_id, pool).await?;
+
+    let users = get_chapter_members(chapter.book_id, chapter.chapter_id, pool).await?;
+    let extensions = chapter_extensions(chapter.chapter_id, pool).await?;
+
+    Ok(crate::view::chapter::admin::extensions(&users, &extensions))
+}
+
 pub async fn unsubmitted_users(
     Extension(chapter): Extension<Chapter>,
     State(state): State<AppStateRef>,
@@ -375,6 +637,7 @@ pub async fn unsubmitted_users(
                                 WHERE
                                     BOOK_ID = $1
                                     AND SUBSCRIPTIONS.USER_ID = USERS.ID
+                                    AND NOT (SUBSCRIPTIONS.ROLE ? 'spectator')
                                     AND COALESCE(
                                         ((SUBSCRIPTIONS.ROLE -> 'guest' -> 'chapter_ids') @> TO_JSONB($2::INT)),
                                         TRUE
@@ -388,6 +651,7 @@ pub async fn unsubmitted_users(
                     ) AS CHAPTER_EVENTS
                     LEFT JOIN PICKS ON BOOK_USERS.ID = PICKS.USER_ID
                         AND CHAPTER_EVENTS.ID = PICKS.EVENT_ID
+                        AND PICKS.ENTRY_ID IS NULL
             ) AS EVENT_PICKS
         GROUP BY EVENT_PICKS.USERNAME
         ORDER BY "all_complete!", username
@@ -0,0 +1,55 @@
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use axum_ctx::StatusCode;
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        chapter::Chapter,
+        commitment::{get_commitments, get_receipt, verify_picks},
+    },
+    AppError, AppNotification, AppStateRef,
+};
+
+pub async fn status(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let commitments = get_commitments(chapter.chapter_id, pool).await?;
+    let verified = verify_picks(chapter.chapter_id, user.id, pool).await?;
+
+    Ok(crate::view::chapter::closed::commitment(
+        &commitments,
+        verified,
+    ))
+}
+
+pub async fn receipt(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+) -> Result<impl IntoResponse, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+
+    let receipt = get_receipt(chapter.chapter_id, user.id, &state.pool)
+        .await
+        .map_err(AppError::from)?
+        .ok_or(AppNotification(
+            StatusCode::NOT_FOUND,
+            "No picks were committed for you in this chapter".into(),
+        ))?;
+
+    Ok((
+        [(
+            "Content-Disposition",
+            format!(
+                "attachment; filename=\"receipt-chapter-{}.json\"",
+                chapter.chapter_id
+            ),
+        )],
+        Json(receipt),
+    ))
+}
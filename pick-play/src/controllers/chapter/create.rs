@@ -171,8 +171,12 @@ pub async fn post(
     let mut transaction = pool.begin().await.map_err(AppError::from)?;
 
     let record = sqlx::query!(
-        "INSERT INTO chapters (title, book_id, is_open)
-        VALUES ($1, $2, false)
+        "INSERT INTO chapters (title, book_id, is_open, position)
+        VALUES ($1, $2, false, (
+            SELECT COALESCE(MAX(position), 0) + 1
+            FROM chapters
+            WHERE book_id = $2
+        ))
         RETURNING id
         ",
         chapter_submission.chapter_name,
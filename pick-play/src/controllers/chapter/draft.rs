@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        chapter::Chapter,
+        draft::{fit_draft, get_draft, has_full_picks, save_draft, DraftPick},
+        entry::get_entry,
+        event::get_events,
+    },
+    AppError, AppStateRef,
+};
+
+/// Drafts are saved on every change, so anything much bigger than a full
+/// pick form is refused
+pub const MAX_DRAFT_BYTES: usize = 64 * 1024;
+
+/// The pick form as it is sent while being filled out, with every field that
+/// hasn't been filled in yet missing
+#[derive(Debug, serde::Deserialize)]
+pub struct DraftSubmission {
+    #[serde(default)]
+    events: Vec<DraftEvent>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DraftEvent {
+    event_id: String,
+    spreads: Option<Vec<DraftSpread>>,
+    user_input: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DraftSpread {
+    selection: Option<String>,
+    num_points: Option<String>,
+}
+
+impl DraftEvent {
+    fn into_pick(self) -> Option<(i32, DraftPick)> {
+        let event_id = self.event_id.parse().ok()?;
+        let pick = match (self.spreads, self.user_input) {
+            (Some(spreads), _) => DraftPick::SpreadGroup {
+                wager: spreads
+                    .iter()
+                    .map(|spread| spread.num_points.as_ref()?.parse().ok())
+                    .collect(),
+                choice: spreads.into_iter().map(|spread| spread.selection).collect(),
+            },
+            (None, choice) => DraftPick::UserInput { choice },
+        };
+        Some((event_id, pick))
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct DraftPath {
+    entry_id: Option<i32>,
+}
+
+/// The entry the draft belongs to, `None` for the user's own
+async fn draft_entry(
+    entry_id: Option<i32>,
+    user_id: i32,
+    chapter: &Chapter,
+    pool: &sqlx::PgPool,
+) -> Result<Option<i32>, AppError<'static>> {
+    match entry_id {
+        Some(entry_id) => get_entry(entry_id, user_id, chapter.book_id, pool)
+            .await?
+            .map(|entry| Some(entry.id))
+            .ok_or(AppError::Unauthorized("You don't own that entry")),
+        None => Ok(None),
+    }
+}
+
+pub async fn status(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Path(DraftPath { entry_id }): Path<DraftPath>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let entry_id = draft_entry(entry_id, user.id, &chapter, pool).await?;
+    let submitted = has_full_picks(chapter.chapter_id, user.id, entry_id, pool).await?;
+    let draft = get_draft(chapter.chapter_id, user.id, entry_id, pool).await?;
+
+    Ok(crate::view::chapter::open::pick_status(
+        submitted,
+        draft.as_ref().map(|draft| draft.saved_at.as_str()),
+    ))
+}
+
+/// Keeps the form as it is, filled out or not, for the chapter's events
+pub async fn save(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Path(DraftPath { entry_id }): Path<DraftPath>,
+    Json(submission): Json<DraftSubmission>,
+) -> Result<(), AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let entry_id = draft_entry(entry_id, user.id, &chapter, pool).await?;
+    let events = get_events(chapter.chapter_id, pool).await?;
+    let draft = fit_draft(
+        &events,
+        submission
+            .events
+            .into_iter()
+            .filter_map(DraftEvent::into_pick),
+    );
+
+    save_draft(chapter.chapter_id, user.id, entry_id, &draft, pool).await?;
+
+    Ok(())
+}
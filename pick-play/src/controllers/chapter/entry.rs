@@ -0,0 +1,120 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use axum_ctx::StatusCode;
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        book::BookSubscription,
+        chapter::Chapter,
+        draft::{apply_draft, get_draft},
+        entry::{get_entry, owned_entries, BookEntry},
+        event::get_picks,
+        extension::has_active_extension,
+        team::get_chapter_teams,
+    },
+    AppError, AppNotification, AppStateRef,
+};
+
+use super::page::{save_picks, PickSubmission};
+
+#[derive(serde::Deserialize)]
+pub struct EntryPath {
+    entry_id: i32,
+}
+
+/// The entry, if `owner_id` opened it in the book
+async fn owned_entry(
+    entry_id: i32,
+    owner_id: i32,
+    book_id: i32,
+    pool: &sqlx::PgPool,
+) -> Result<BookEntry, AppNotification> {
+    get_entry(entry_id, owner_id, book_id, pool)
+        .await
+        .map_err(AppError::from)?
+        .ok_or(AppNotification(
+            StatusCode::NOT_FOUND,
+            "You don't have that entry".into(),
+        ))
+}
+
+pub async fn get(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Extension(chapter): Extension<Chapter>,
+    Path(EntryPath { entry_id }): Path<EntryPath>,
+) -> Result<maud::Markup, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let entry = owned_entry(entry_id, user.id, chapter.book_id, pool).await?;
+    let is_extended = !chapter.is_open
+        && has_active_extension(chapter.chapter_id, user.id, pool)
+            .await
+            .map_err(AppError::from)?;
+    if !chapter.is_open && !is_extended {
+        return Err(AppNotification(
+            StatusCode::LOCKED,
+            "This chapter is closed".into(),
+        ));
+    }
+
+    let picks = get_picks(user.id, Some(entry.id), chapter.chapter_id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let picks = match get_draft(chapter.chapter_id, user.id, Some(entry.id), pool)
+        .await
+        .map_err(AppError::from)?
+    {
+        Some(draft) => apply_draft(picks, user.id, &draft.contents),
+        None => picks,
+    };
+    let teams = get_chapter_teams(chapter.chapter_id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let entries = owned_entries(user.id, chapter.book_id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::chapter::open::entry(
+        &user.username,
+        &book_subscription.name,
+        &chapter,
+        &entry,
+        picks,
+        &teams,
+        &entries,
+    ))
+}
+
+pub async fn submit(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Path(EntryPath { entry_id }): Path<EntryPath>,
+    picks: Result<Json<PickSubmission>, axum::extract::rejection::JsonRejection>,
+) -> Result<AppNotification, AppNotification> {
+    let Ok(Json(picks)) = picks else {
+        tracing::debug!("Could not deserialize entry picks: {picks:?}");
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Can't Process Picks. Are they all the way filled out?".into(),
+        ));
+    };
+
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let entry = owned_entry(entry_id, user.id, chapter.book_id, pool).await?;
+
+    save_picks(&chapter, user.id, Some(entry.id), None, picks, pool).await?;
+
+    Ok(AppNotification(
+        StatusCode::OK,
+        format!("Picks Saved for {}", entry.name),
+    ))
+}
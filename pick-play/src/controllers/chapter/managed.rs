@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use axum_ctx::StatusCode;
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        book::BookSubscription, chapter::Chapter, entry::owned_entries, event::get_picks,
+        extension::has_active_extension, managed::owned_users, team::get_chapter_teams,
+    },
+    AppError, AppNotification, AppStateRef,
+};
+
+use super::page::{save_picks, PickSubmission};
+
+#[derive(serde::Deserialize)]
+pub struct ManagedPath {
+    book_id: i32,
+    chapter_id: i32,
+    user_id: i32,
+}
+
+/// The managed user's name, if `owner_id` makes their picks in the book
+async fn owned_username(
+    owner_id: i32,
+    user_id: i32,
+    book_id: i32,
+    pool: &sqlx::PgPool,
+) -> Result<String, AppNotification> {
+    owned_users(owner_id, book_id, pool)
+        .await
+        .map_err(AppError::from)?
+        .into_iter()
+        .find_map(|(id, username)| (id == user_id).then_some(username))
+        .ok_or(AppNotification(
+            StatusCode::NOT_FOUND,
+            "You don't make picks for that member".into(),
+        ))
+}
+
+pub async fn get(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Extension(chapter): Extension<Chapter>,
+    Path(ManagedPath {
+        book_id: _b,
+        chapter_id: _c,
+        user_id,
+    }): Path<ManagedPath>,
+) -> Result<maud::Markup, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let managed_username = owned_username(user.id, user_id, chapter.book_id, pool).await?;
+    // The managed member's extension counts, not the owner's
+    let is_extended = !chapter.is_open
+        && has_active_extension(chapter.chapter_id, user_id, pool)
+            .await
+            .map_err(AppError::from)?;
+    if !chapter.is_open && !is_extended {
+        return Err(AppNotification(
+            StatusCode::LOCKED,
+            "This chapter is closed".into(),
+        ));
+    }
+
+    let picks = get_picks(user_id, None, chapter.chapter_id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let teams = get_chapter_teams(chapter.chapter_id, pool)
+        .await
+        .map_err(AppError::from)?;
+    let entries = owned_entries(user.id, chapter.book_id, pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::chapter::open::managed(
+        &user.username,
+        &book_subscription.name,
+        &chapter,
+        (user_id, &managed_username),
+        picks,
+        &teams,
+        &entries,
+    ))
+}
+
+pub async fn submit(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Path(ManagedPath {
+        book_id: _b,
+        chapter_id: _c,
+        user_id,
+    }): Path<ManagedPath>,
+    picks: Result<Json<PickSubmission>, axum::extract::rejection::JsonRejection>,
+) -> Result<AppNotification, AppNotification> {
+    let Ok(Json(picks)) = picks else {
+        tracing::debug!("Could not deserialize managed picks: {picks:?}");
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Can't Process Picks. Are they all the way filled out?".into(),
+        ));
+    };
+
+    let owner = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let managed_username = owned_username(owner.id, user_id, chapter.book_id, pool).await?;
+
+    save_picks(&chapter, user_id, None, Some(owner.id), picks, pool).await?;
+
+    Ok(AppNotification(
+        StatusCode::OK,
+        format!("Picks Saved for {managed_username}"),
+    ))
+}
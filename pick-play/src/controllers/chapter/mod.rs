@@ -1,3 +1,4 @@
+use axum::extract::DefaultBodyLimit;
 use axum::routing::MethodRouter;
 use axum::{
     handler::Handler as _,
@@ -6,20 +7,39 @@ use axum::{
     Extension, Router,
 };
 
-use crate::{auth, model, AppStateRef};
+use crate::{auth, model, model::book::BookCapability, AppStateRef};
 
 use super::book;
 
 pub mod admin;
+pub mod commitment;
 pub mod create;
+pub mod draft;
+pub mod entry;
+pub mod managed;
 pub mod page;
+pub mod projection;
+pub mod proxy;
+pub mod recap;
+pub mod revisions;
 
 async fn get_chapter_home(
     auth_session: auth::AuthSession,
     Extension(book_subscription): Extension<model::book::BookSubscription>,
     Extension(chapter): Extension<model::chapter::Chapter>,
 ) -> impl axum::response::IntoResponse {
-    if chapter.is_open {
+    let is_extended = match &auth_session.user {
+        Some(user) if !chapter.is_open => model::extension::has_active_extension(
+            chapter.chapter_id,
+            user.id,
+            &auth_session.backend.0,
+        )
+        .await
+        .unwrap_or_default(),
+        _ => false,
+    };
+
+    if chapter.is_open || is_extended {
         page::open_book(auth_session, &book_subscription, &chapter).await
     } else {
         page::closed_book(auth_session, &book_subscription, &chapter).await
@@ -40,14 +60,107 @@ pub fn router() -> Router<AppStateRef> {
                 .nest(
                     "/admin/",
                     Router::new()
-                        .route("/", get(admin::get).post(admin::post).delete(admin::delete))
-                        .route("/user-input", get(admin::user_input))
-                        .route("/open", post(admin::open))
-                        .route("/visible", post(admin::visible))
+                        .route(
+                            "/",
+                            get(admin::get)
+                                .post(admin::post.layer(middleware::from_fn_with_state(
+                                    BookCapability::Grade,
+                                    book::mw::require_capability,
+                                )))
+                                .delete(admin::delete.layer(middleware::from_fn_with_state(
+                                    BookCapability::ManageChapters,
+                                    book::mw::require_capability,
+                                ))),
+                        )
+                        .merge(
+                            Router::new()
+                                .route("/user-input", get(admin::user_input))
+                                .route("/history", get(admin::history))
+                                .route("/history/{change_id}/revert", post(admin::revert))
+                                .route_layer(middleware::from_fn_with_state(
+                                    BookCapability::Grade,
+                                    book::mw::require_capability,
+                                )),
+                        )
+                        .merge(
+                            Router::new()
+                                .route("/proxy", get(proxy::picker))
+                                .route("/revisions", get(revisions::admin))
+                                .route(
+                                    "/proxy/{user_id}",
+                                    post(
+                                        proxy::submit
+                                            .layer(middleware::from_fn(mw::confirm_chapter_open)),
+                                    ),
+                                )
+                                .route_layer(middleware::from_fn_with_state(
+                                    BookCapability::ManageMembers,
+                                    book::mw::require_capability,
+                                )),
+                        )
+                        .merge(
+                            Router::new()
+                                .route("/open", post(admin::open))
+                                .route(
+                                    "/extensions",
+                                    get(admin::extensions)
+                                        .post(admin::grant)
+                                        .delete(admin::revoke),
+                                )
+                                .route("/visible", post(admin::visible))
+                                .route("/order", get(admin::order).post(admin::reorder))
+                                .route_layer(middleware::from_fn_with_state(
+                                    BookCapability::ManageChapters,
+                                    book::mw::require_capability,
+                                )),
+                        )
                         .route("/unsubmitted-users", get(admin::unsubmitted_users)),
                 )
-                .route_layer(middleware::from_fn(book::mw::require_admin))
+                .route_layer(middleware::from_fn(book::mw::require_any_capability))
                 .route("/", chapter_home_page)
+                .merge(
+                    Router::new()
+                        .merge(
+                            Router::new()
+                                .route("/recap", get(recap::handler))
+                                .route("/what-if", get(projection::handler))
+                                .route("/what-if/standings", get(projection::standings))
+                                .route_layer(middleware::from_fn(mw::hide_while_extended)),
+                        )
+                        .route(
+                            "/draft",
+                            get(draft::status).post(
+                                draft::save
+                                    .layer(middleware::from_fn(mw::confirm_chapter_open))
+                                    .layer(DefaultBodyLimit::max(draft::MAX_DRAFT_BYTES)),
+                            ),
+                        )
+                        .route(
+                            "/entry/{entry_id}/",
+                            get(entry::get).post(
+                                entry::submit.layer(middleware::from_fn(mw::confirm_chapter_open)),
+                            ),
+                        )
+                        .route(
+                            "/entry/{entry_id}/draft",
+                            get(draft::status).post(
+                                draft::save
+                                    .layer(middleware::from_fn(mw::confirm_chapter_open))
+                                    .layer(DefaultBodyLimit::max(draft::MAX_DRAFT_BYTES)),
+                            ),
+                        )
+                        .route(
+                            "/for/{user_id}",
+                            get(managed::get).post(
+                                managed::submit
+                                    .layer(middleware::from_fn(mw::confirm_chapter_open)),
+                            ),
+                        )
+                        .route("/commitment", get(commitment::status))
+                        .route("/receipt", get(commitment::receipt))
+                        .route("/revisions", get(revisions::handler))
+                        .route_layer(middleware::from_fn(mw::confirm_user_access)),
+                )
                 .route_layer(middleware::from_fn(mw::chapter_ext)),
         )
         .nest(
@@ -56,7 +169,10 @@ pub fn router() -> Router<AppStateRef> {
                 .route("/", get(create::get).post(create::post))
                 .route("/add", get(create::add_event))
                 .route("/team-select", post(create::team_select))
-                .route_layer(middleware::from_fn(book::mw::require_admin)),
+                .route_layer(middleware::from_fn_with_state(
+                    BookCapability::ManageChapters,
+                    book::mw::require_capability,
+                )),
         )
 }
 
@@ -72,7 +188,7 @@ pub mod mw {
 
     use crate::{
         auth::{AuthSession, BackendPgDB},
-        model::chapter::get_chapter,
+        model::{chapter::get_chapter, extension::has_active_extension},
     };
 
     #[derive(serde::Deserialize)]
@@ -108,10 +224,16 @@ pub mod mw {
         next: axum::middleware::Next,
     ) -> Result<Response<Body>, ErrorResponse> {
         match book_subscription.role {
-            crate::model::book::BookRole::Owner | crate::model::book::BookRole::Admin => {
+            ref role if role.has_capability(crate::model::book::BookCapability::ManageChapters) => {
+                Ok(next.run(request).await)
+            }
+            crate::model::book::BookRole::Participant
+            | crate::model::book::BookRole::Moderator { .. }
+                if chapter.is_visible =>
+            {
                 Ok(next.run(request).await)
             }
-            crate::model::book::BookRole::Participant if chapter.is_visible => {
+            crate::model::book::BookRole::Spectator if chapter.is_visible && !chapter.is_open => {
                 Ok(next.run(request).await)
             }
             crate::model::book::BookRole::Guest {
@@ -123,12 +245,32 @@ pub mod mw {
         }
     }
 
+    #[derive(serde::Deserialize)]
+    pub struct SubmitPath {
+        user_id: Option<i32>,
+    }
+
+    /// Members with a deadline extension can keep submitting after the
+    /// chapter locks. Routes that submit for someone else check that
+    /// member's extension rather than the submitter's.
     pub(super) async fn confirm_chapter_open(
+        auth_session: AuthSession,
         Extension(chapter): Extension<crate::model::chapter::Chapter>,
+        Path(SubmitPath { user_id }): Path<SubmitPath>,
         request: Request,
         next: axum::middleware::Next,
     ) -> Result<Response<Body>, ErrorResponse> {
-        if chapter.is_open {
+        let target_id = user_id.or(auth_session.user.as_ref().map(|user| user.id));
+        let is_extended = match target_id {
+            Some(target_id) if !chapter.is_open => {
+                has_active_extension(chapter.chapter_id, target_id, &auth_session.backend.0)
+                    .await
+                    .map_err(crate::AppError::from)?
+            }
+            _ => false,
+        };
+
+        if chapter.is_open || is_extended {
             Ok(next.run(request).await)
         } else {
             Err(crate::AppNotification(
@@ -138,4 +280,28 @@ pub mod mw {
             .into())
         }
     }
+
+    /// Sends members that are still submitting under an extension back to
+    /// their picks instead of showing them everyone else's
+    pub(super) async fn hide_while_extended(
+        auth_session: AuthSession,
+        Extension(chapter): Extension<crate::model::chapter::Chapter>,
+        request: Request,
+        next: axum::middleware::Next,
+    ) -> Result<Response<Body>, ErrorResponse> {
+        let user = auth_session.user.ok_or(crate::AppError::BackendUser)?;
+
+        if has_active_extension(chapter.chapter_id, user.id, &auth_session.backend.0)
+            .await
+            .map_err(crate::AppError::from)?
+        {
+            return Err(Redirect::to(&format!(
+                "/book/{}/chapter/{}/",
+                chapter.book_id, chapter.chapter_id
+            ))
+            .into());
+        }
+
+        Ok(next.run(request).await)
+    }
 }
@@ -1,6 +1,9 @@
-use crate::model::book::BookRole;
 use crate::model::chapter::get_chapter_users;
+use crate::model::commitment::commit_picks;
+use crate::model::draft::{apply_draft, get_draft};
+use crate::model::entry::owned_entries;
 use crate::model::event::{get_chapter_picks, get_events, get_picks};
+use crate::model::grading::grade_picks;
 use crate::model::team::get_chapter_teams;
 
 use crate::AppNotification;
@@ -22,19 +25,31 @@ pub async fn open_book(
     let user = auth_session.user.ok_or(AppError::BackendUser)?;
     let BackendPgDB(pool) = auth_session.backend;
 
-    let user_picks = get_picks(user.id, chapter.chapter_id, &pool);
+    let user_picks = get_picks(user.id, None, chapter.chapter_id, &pool);
     let relevent_teams = get_chapter_teams(chapter.chapter_id, &pool);
+    let entries = owned_entries(user.id, chapter.book_id, &pool);
 
     let user_picks = user_picks.await.map_err(AppError::from)?;
     let relevent_teams = relevent_teams.await.map_err(AppError::from)?;
+    let entries = entries.await.map_err(AppError::from)?;
+
+    // Pick up where the user left off rather than at their last submission
+    let user_picks = match get_draft(chapter.chapter_id, user.id, None, &pool)
+        .await
+        .map_err(AppError::from)?
+    {
+        Some(draft) => apply_draft(user_picks, user.id, &draft.contents),
+        None => user_picks,
+    };
 
     Ok(crate::view::chapter::open::m(
         &user.username,
         &book_subscription.name,
         chapter,
         user_picks,
-        book_subscription.role == BookRole::Admin,
+        book_subscription.role.has_any_capability(),
         relevent_teams,
+        &entries,
     ))
 }
 
@@ -83,30 +98,134 @@ pub async fn submit(
     let user_id = auth_session.user.ok_or(AppError::BackendUser)?.id;
     let pool = auth_session.backend.0;
 
-    let (event_ids, choices, wagers) = validate_picks(picks.events, &pool).await?;
+    save_picks(&chapter, user_id, None, None, picks, &pool).await?;
+
+    Ok(AppNotification(StatusCode::OK, "Picks Saved".into()))
+}
+
+/// Validates and stores `user_id`'s picks, for one of their extra entries when
+/// `entry_id` is set. `entered_by` is the admin entering them on the member's
+/// behalf, and is cleared when the member submits their own picks again.
+pub async fn save_picks(
+    chapter: &Chapter,
+    user_id: i32,
+    entry_id: Option<i32>,
+    entered_by: Option<i32>,
+    picks: PickSubmission,
+    pool: &sqlx::PgPool,
+) -> Result<(), RespErr> {
+    let (event_ids, choices, wagers) = validate_picks(picks.events, pool).await?;
+
+    let mut transaction = pool.begin().await.map_err(AppError::from)?;
 
     sqlx::query!(
         r#"
-        INSERT INTO picks (book_id, chapter_id, user_id, event_id, choice, wager)
-        SELECT $1 AS book_id, $2 AS chapter_id, $3 AS user_id, event_id, choice, wager
+        INSERT INTO picks (book_id, chapter_id, user_id, entry_id, event_id, choice, wager, entered_by, entered_at)
+        SELECT
+            $1 AS book_id,
+            $2 AS chapter_id,
+            $3 AS user_id,
+            $8::INT AS entry_id,
+            event_id,
+            choice,
+            wager,
+            $7::INT AS entered_by,
+            CASE WHEN $7::INT IS NULL THEN NULL ELSE CURRENT_TIMESTAMP END AS entered_at
         FROM UNNEST($4::INT[], $5::JSONB[], $6::JSONB[]) AS a(event_id, choice, wager)
-        ON CONFLICT (book_id, chapter_id, event_id, user_id)
+        ON CONFLICT (book_id, chapter_id, event_id, user_id, entry_id)
         DO UPDATE SET
             choice = EXCLUDED.choice,
-            wager = EXCLUDED.wager
+            wager = EXCLUDED.wager,
+            entered_by = EXCLUDED.entered_by,
+            entered_at = EXCLUDED.entered_at,
+            auto_policy = NULL,
+            points = NULL
         "#,
         chapter.book_id,
         chapter.chapter_id,
         user_id,
         &event_ids,
         &choices,
-        &wagers
+        &wagers,
+        entered_by,
+        entry_id
     )
-    .execute(&pool)
+    .execute(&mut *transaction)
     .await
     .map_err(AppError::from)?;
 
-    Ok(AppNotification(StatusCode::OK, "Picks Saved".into()))
+    // Kept forever so there is a record of when every pick was made
+    sqlx::query!(
+        r#"
+        INSERT INTO pick_revisions (book_id, chapter_id, user_id, entry_id, event_id, choice, wager, entered_by)
+        SELECT $1 AS book_id, $2 AS chapter_id, $3 AS user_id, $8::INT, event_id, choice, wager, $7::INT
+        FROM UNNEST($4::INT[], $5::JSONB[], $6::JSONB[]) AS a(event_id, choice, wager)
+        "#,
+        chapter.book_id,
+        chapter.chapter_id,
+        user_id,
+        &event_ids,
+        &choices,
+        &wagers,
+        entered_by,
+        entry_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(AppError::from)?;
+
+    sqlx::query!(
+        "DELETE FROM pick_drafts WHERE chapter_id = $1 AND user_id = $2 AND entry_id IS NOT DISTINCT FROM $3",
+        chapter.chapter_id,
+        user_id,
+        entry_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(AppError::from)?;
+
+    // Picks made under a deadline extension replace any filled in at lock,
+    // along with the score a missed-pick policy gave them. Only the member's
+    // own entry is ever filled in.
+    if entry_id.is_none() {
+        sqlx::query!(
+            "DELETE FROM missed_picks WHERE chapter_id = $1 AND user_id = $2",
+            chapter.chapter_id,
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(AppError::from)?;
+
+        sqlx::query!(
+        "DELETE FROM added_points WHERE chapter_id = $1 AND user_id = $2 AND auto_policy IS NOT NULL",
+        chapter.chapter_id,
+        user_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(AppError::from)?;
+    }
+
+    // The chapter already published its picks, so late picks are graded
+    // against any answers so far and get a new commitment rather than
+    // quietly differing from the last one
+    if !chapter.is_open {
+        grade_picks(chapter.chapter_id, &event_ids, &mut transaction)
+            .await
+            .map_err(AppError::from)?;
+        commit_picks(chapter.chapter_id, Some(user_id), &mut transaction)
+            .await
+            .map_err(AppError::from)?;
+    }
+
+    transaction.commit().await.map_err(AppError::from)?;
+
+    if !chapter.is_open {
+        super::admin::after_grading(chapter, pool).await;
+    }
+
+    Ok(())
 }
 
 async fn validate_picks(
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    Extension,
+};
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        book::BookSubscription,
+        chapter::Chapter,
+        event::{get_chapter_picks, get_events, Event},
+        projection::{project, projection_members, remaining_games, Projection},
+        team::get_chapter_teams,
+    },
+    AppError, AppStateRef,
+};
+
+async fn projections(
+    chapter: &Chapter,
+    events: &[Event],
+    outcomes: &HashMap<String, String>,
+    pool: &'static sqlx::PgPool,
+) -> Result<Vec<Projection>, AppError<'static>> {
+    // Picks are hidden until the chapter locks
+    if chapter.is_open {
+        return Err(AppError::Unauthorized(
+            "Projections are available once the chapter is closed",
+        ));
+    }
+
+    let members = projection_members(chapter.book_id, chapter.chapter_id, pool).await?;
+    let picks = get_chapter_picks(chapter.chapter_id, pool).await?;
+
+    Ok(project(&members, events, &picks, outcomes))
+}
+
+pub async fn handler(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Extension(chapter): Extension<Chapter>,
+    Query(outcomes): Query<HashMap<String, String>>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let events = get_events(chapter.chapter_id, pool).await?;
+    let projections = projections(&chapter, &events, &outcomes, pool).await?;
+    let teams = get_chapter_teams(chapter.chapter_id, pool).await?;
+
+    Ok(crate::view::chapter::projection::m(
+        &user.username,
+        &book_subscription,
+        &chapter,
+        &remaining_games(&events),
+        &teams,
+        &projections,
+    ))
+}
+
+pub async fn standings(
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Query(outcomes): Query<HashMap<String, String>>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    let events = get_events(chapter.chapter_id, pool).await?;
+    let projections = projections(&chapter, &events, &outcomes, pool).await?;
+
+    Ok(crate::view::chapter::projection::standings(&projections))
+}
@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use axum_ctx::StatusCode;
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        chapter::{get_chapter_members, Chapter},
+        event::get_picks,
+        team::get_chapter_teams,
+    },
+    AppError, AppNotification, AppStateRef,
+};
+
+use super::page::{save_picks, PickSubmission};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ProxyParams {
+    user_id: Option<String>,
+}
+
+/// Member picker on the admin page, with the chosen member's pick form
+pub async fn picker(
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Query(ProxyParams { user_id }): Query<ProxyParams>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    let users = get_chapter_members(chapter.book_id, chapter.chapter_id, pool).await?;
+    let selected = user_id
+        .and_then(|user_id| user_id.parse::<i32>().ok())
+        .filter(|user_id| users.iter().any(|user| user.user_id == *user_id));
+
+    let picks = match selected {
+        Some(user_id) => Some(get_picks(user_id, None, chapter.chapter_id, pool).await?),
+        None => None,
+    };
+    let teams = get_chapter_teams(chapter.chapter_id, pool).await?;
+
+    Ok(crate::view::chapter::admin::proxy_picker(
+        chapter.is_open,
+        &users,
+        selected,
+        picks,
+        &teams,
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct ProxyPath {
+    book_id: i32,
+    chapter_id: i32,
+    user_id: i32,
+}
+
+pub async fn submit(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Path(ProxyPath {
+        book_id: _b,
+        chapter_id: _c,
+        user_id,
+    }): Path<ProxyPath>,
+    picks: Result<Json<PickSubmission>, axum::extract::rejection::JsonRejection>,
+) -> Result<AppNotification, AppNotification> {
+    let Ok(Json(picks)) = picks else {
+        tracing::debug!("Could not deserialize proxy picks: {picks:?}");
+        return Err(AppNotification(
+            StatusCode::BAD_REQUEST,
+            "Can't Process Picks. Are they all the way filled out?".into(),
+        ));
+    };
+
+    let admin = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let users = get_chapter_members(chapter.book_id, chapter.chapter_id, pool).await?;
+    let Some(member) = users.iter().find(|user| user.user_id == user_id) else {
+        return Err(AppNotification(
+            StatusCode::NOT_FOUND,
+            "That member can't make picks in this chapter".into(),
+        ));
+    };
+
+    save_picks(&chapter, user_id, None, Some(admin.id), picks, pool).await?;
+
+    Ok(AppNotification(
+        StatusCode::OK,
+        format!("Picks Saved for {}", member.username),
+    ))
+}
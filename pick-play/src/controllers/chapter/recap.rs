@@ -0,0 +1,24 @@
+use axum::{extract::State, Extension};
+
+use crate::{
+    auth::AuthSession,
+    model::{book::BookSubscription, chapter::Chapter, recap::get_recap},
+    AppError, AppStateRef,
+};
+
+pub async fn handler(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Extension(chapter): Extension<Chapter>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let recap = get_recap(chapter.chapter_id, &state.pool).await?;
+
+    Ok(crate::view::chapter::recap::m(
+        &user.username,
+        &book_subscription,
+        &chapter,
+        recap.as_ref(),
+    ))
+}
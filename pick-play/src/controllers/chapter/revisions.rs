@@ -0,0 +1,65 @@
+use axum::{
+    extract::{Query, State},
+    Extension,
+};
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        book::BookSubscription,
+        chapter::Chapter,
+        event::get_events,
+        pick_revision::{pick_revisions, revision_users},
+        team::get_chapter_teams,
+    },
+    AppError, AppStateRef,
+};
+
+pub async fn handler(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Extension(chapter): Extension<Chapter>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let revisions = pick_revisions(chapter.chapter_id, user.id, pool).await?;
+    let events = get_events(chapter.chapter_id, pool).await?;
+    let teams = get_chapter_teams(chapter.chapter_id, pool).await?;
+
+    Ok(crate::view::chapter::revisions::m(
+        &user.username,
+        &book_subscription,
+        &chapter,
+        &revisions,
+        &events,
+        &teams,
+    ))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RevisionParams {
+    user_id: Option<String>,
+}
+
+pub async fn admin(
+    State(state): State<AppStateRef>,
+    Extension(chapter): Extension<Chapter>,
+    Query(RevisionParams { user_id }): Query<RevisionParams>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let pool = &state.pool;
+
+    let user_id = user_id.and_then(|user_id| user_id.parse().ok());
+    let users = revision_users(chapter.chapter_id, pool).await?;
+    let revisions = match user_id {
+        Some(user_id) => pick_revisions(chapter.chapter_id, user_id, pool).await?,
+        None => vec![],
+    };
+    let events = get_events(chapter.chapter_id, pool).await?;
+    let teams = get_chapter_teams(chapter.chapter_id, pool).await?;
+
+    Ok(crate::view::chapter::revisions::picker(
+        &users, user_id, &revisions, &events, &teams,
+    ))
+}
@@ -6,9 +6,9 @@ use axum::{
 use axum_ctx::RespErr;
 use reqwest::StatusCode;
 
-use crate::{auth::AuthSession, AppError, AppNotification};
+use crate::{auth::AuthSession, model::invite::redeem_invite, AppError, AppNotification};
 
-use super::session::OauthProfile;
+use super::{invite::INVITE_COOKIE, session::OauthProfile};
 
 pub async fn finish_page(
     cookie_jar: axum_extra::extract::CookieJar,
@@ -133,6 +133,14 @@ pub async fn post(
     .await
     .map_err(AppError::from)?;
 
+    let location = match cookie_jar.get(INVITE_COOKIE) {
+        Some(invite_code) => redeem_invite(invite_code.value(), user.id, &mut transaction)
+            .await
+            .map_err(AppError::from)?
+            .map(|book_id| format!("/book/{book_id}/")),
+        None => None,
+    };
+
     auth_session
         .login(&user)
         .await
@@ -140,5 +148,9 @@ pub async fn post(
 
     transaction.commit().await.map_err(AppError::from)?;
 
-    Ok((cookie_jar.remove("signup_token"), [("HX-Location", "/")]).into_response())
+    Ok((
+        cookie_jar.remove("signup_token").remove(INVITE_COOKIE),
+        [("HX-Location", location.unwrap_or_else(|| "/".to_string()))],
+    )
+        .into_response())
 }
@@ -2,7 +2,7 @@ use axum::response::ErrorResponse;
 
 use crate::{
     auth::{authz::has_perm, AuthSession},
-    model::book::user_books_stats,
+    model::{book::user_books_stats, join_request::user_join_requests},
     AppError,
 };
 
@@ -14,7 +14,16 @@ pub async fn handler(session: AuthSession) -> Result<maud::Markup, ErrorResponse
         .await
         .map_err(AppError::from)?;
 
+    let join_requests = user_join_requests(user.id, &pool)
+        .await
+        .map_err(AppError::from)?;
+
     let is_admin = has_perm("admin", user.id, &pool).await.unwrap_or(false);
 
-    Ok(crate::view::home::m(&user.username, is_admin, book_stats))
+    Ok(crate::view::home::m(
+        &user.username,
+        is_admin,
+        book_stats,
+        &join_requests,
+    ))
 }
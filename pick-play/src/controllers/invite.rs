@@ -0,0 +1,66 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Redirect, Response},
+    Form,
+};
+use axum_extra::extract::CookieJar;
+use reqwest::StatusCode;
+
+use crate::{
+    auth::AuthSession, model::invite::redeem_invite, AppError, AppNotification, AppStateRef,
+};
+
+pub const INVITE_COOKIE: &str = "invite_code";
+
+pub fn invite_cookie(code: &str) -> tower_sessions::cookie::Cookie<'static> {
+    tower_sessions::cookie::Cookie::build((INVITE_COOKIE, code.to_string()))
+        .http_only(true)
+        .same_site(tower_sessions::cookie::SameSite::Lax)
+        .path("/")
+        .build()
+}
+
+/// Opening an invite link while logged out stashes the code in a cookie so
+/// that it can be redeemed once the user logs in or finishes signing up
+pub async fn join_link(
+    auth_session: AuthSession,
+    cookie_jar: CookieJar,
+    State(state): State<AppStateRef>,
+    Path(code): Path<String>,
+) -> Result<Response, AppError<'static>> {
+    let Some(user) = auth_session.user else {
+        return Ok((cookie_jar.add(invite_cookie(&code)), Redirect::to("/login")).into_response());
+    };
+
+    let mut conn = state.pool.acquire().await?;
+
+    match redeem_invite(&code, user.id, &mut conn).await? {
+        Some(book_id) => Ok(Redirect::to(&format!("/book/{book_id}/")).into_response()),
+        None => Ok(crate::view::invite::invalid(&user.username).into_response()),
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct JoinCodeForm {
+    code: String,
+}
+
+pub async fn join_code(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Form(JoinCodeForm { code }): Form<JoinCodeForm>,
+) -> Result<impl IntoResponse, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+
+    let mut conn = state.pool.acquire().await.map_err(AppError::from)?;
+
+    let book_id = redeem_invite(&code, user.id, &mut conn)
+        .await
+        .map_err(AppError::from)?
+        .ok_or(AppNotification(
+            StatusCode::NOT_FOUND,
+            "That join code is invalid or has expired".into(),
+        ))?;
+
+    Ok([("HX-Redirect", format!("/book/{book_id}/"))])
+}
@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Redirect, Response},
+};
+use reqwest::StatusCode;
+
+use crate::{
+    auth::AuthSession,
+    model::join_request::{dismiss_join_request, get_requestable_book, request_to_join},
+    AppError, AppNotification, AppStateRef,
+};
+
+pub async fn page(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Path(book_id): Path<i32>,
+) -> Result<Response, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+
+    let Some((book_name, is_member)) = get_requestable_book(book_id, user.id, &state.pool).await?
+    else {
+        return Ok(Redirect::to("/").into_response());
+    };
+
+    if is_member {
+        return Ok(Redirect::to(&format!("/book/{book_id}/")).into_response());
+    }
+
+    Ok(crate::view::join_request::m(&user.username, book_id, &book_name).into_response())
+}
+
+pub async fn submit(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Path(book_id): Path<i32>,
+) -> Result<AppNotification, AppNotification> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+
+    let (_, is_member) = get_requestable_book(book_id, user.id, &state.pool)
+        .await
+        .map_err(AppError::from)?
+        .ok_or(AppNotification(
+            StatusCode::NOT_FOUND,
+            "Could not find book".into(),
+        ))?;
+
+    if is_member {
+        return Err(AppNotification(
+            StatusCode::CONFLICT,
+            "You are already in this book".into(),
+        ));
+    }
+
+    request_to_join(book_id, user.id, &state.pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(AppNotification(StatusCode::OK, "Request Sent".into()))
+}
+
+pub async fn dismiss(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Path(request_id): Path<i32>,
+) -> Result<(), AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+
+    dismiss_join_request(request_id, user.id, &state.pool).await?;
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use axum::extract::Path;
+use axum_ctx::{RespErr, StatusCode};
+
+use crate::{
+    auth::{AuthSession, BackendPgDB},
+    model::profile::{chapter_results, get_username, shared_books, spread_results},
+    AppError,
+};
+
+pub async fn handler(
+    auth_session: AuthSession,
+    Path(user_id): Path<i32>,
+) -> Result<maud::Markup, RespErr> {
+    let viewer = auth_session.user.ok_or(AppError::BackendUser)?;
+    let BackendPgDB(pool) = auth_session.backend;
+    let not_found = || RespErr::new(StatusCode::NOT_FOUND).user_msg("User not found");
+
+    let username = get_username(user_id, &pool)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(not_found)?;
+
+    // Profiles are only visible to members who share a book with the user
+    let books = shared_books(viewer.id, user_id, &pool)
+        .await
+        .map_err(AppError::from)?;
+    if books.is_empty() && viewer.id != user_id {
+        return Err(not_found());
+    }
+    let book_ids = books.iter().map(|book| book.id).collect::<Vec<_>>();
+
+    let chapters = chapter_results(user_id, &book_ids, &pool)
+        .await
+        .map_err(AppError::from)?;
+    let spreads = spread_results(user_id, &book_ids, &pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(crate::view::profile::m(
+        &viewer.username,
+        &username,
+        &books,
+        &chapters,
+        &spreads,
+    ))
+}
@@ -78,7 +78,10 @@ pub mod google {
     use oauth2::TokenResponse as _;
     use reqwest::StatusCode;
 
-    use crate::{auth::AuthSession, AppError};
+    use crate::{
+        auth::AuthSession, controllers::invite::INVITE_COOKIE, model::invite::redeem_invite,
+        AppError,
+    };
 
     #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
     pub struct GoogleOauth {
@@ -160,7 +163,21 @@ pub mod google {
                 .await
                 .ctx(StatusCode::INTERNAL_SERVER_ERROR)
                 .log_msg("Could not log in via google oauth")?;
-            return Err(Redirect::to("/").into());
+
+            let Some(invite_code) = cookie_jar.get(INVITE_COOKIE) else {
+                return Err(Redirect::to("/").into());
+            };
+
+            let mut conn = pool.acquire().await.map_err(AppError::from)?;
+            let redirect = match redeem_invite(invite_code.value(), user.id, &mut conn)
+                .await
+                .map_err(AppError::from)?
+            {
+                Some(book_id) => Redirect::to(&format!("/book/{book_id}/")),
+                None => Redirect::to("/"),
+            };
+
+            return Ok((cookie_jar.remove(INVITE_COOKIE), redirect).into_response());
         }
 
         let content = serde_json::to_value(profile.clone())
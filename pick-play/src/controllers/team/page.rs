@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Path, State},
+    Extension,
+};
+
+use crate::{
+    auth::AuthSession,
+    model::{
+        book::BookSubscription,
+        team::{book_team_games, get},
+    },
+    AppError, AppStateRef,
+};
+
+#[derive(serde::Deserialize)]
+pub struct TeamIdPath {
+    book_id: i32,
+    team_id: i32,
+}
+
+pub async fn handler(
+    auth_session: AuthSession,
+    State(state): State<AppStateRef>,
+    Extension(book_subscription): Extension<BookSubscription>,
+    Path(TeamIdPath {
+        book_id: _b,
+        team_id,
+    }): Path<TeamIdPath>,
+) -> Result<maud::Markup, AppError<'static>> {
+    let user = auth_session.user.ok_or(AppError::BackendUser)?;
+    let pool = &state.pool;
+
+    let team = get(team_id, pool)
+        .await?
+        .ok_or(AppError::Parse("Team not found"))?;
+    let games = book_team_games(team_id, book_subscription.id, pool).await?;
+
+    Ok(crate::view::team::m(
+        &user.username,
+        &book_subscription,
+        &team,
+        &games,
+    ))
+}
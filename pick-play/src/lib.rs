@@ -5,7 +5,7 @@ use {
     auth::BackendPgDB,
     axum::{
         response::{Html, IntoResponse},
-        routing::get,
+        routing::{delete, get, post},
         Router,
     },
     axum_ctx::{RespErr, StatusCode},
@@ -18,16 +18,36 @@ pub mod controllers {
     pub mod chapter;
     pub mod finish_signup;
     pub mod home;
+    pub mod invite;
+    pub mod join_request;
+    pub mod profile;
     pub mod session;
     pub mod signup;
     pub mod team;
 }
 
 pub mod model {
+    pub mod achievement;
     pub mod book;
     pub mod chapter;
+    pub mod commitment;
+    pub mod compare;
+    pub mod consensus;
+    pub mod draft;
+    pub mod entry;
     pub mod event;
+    pub mod extension;
+    pub mod grading;
+    pub mod group;
+    pub mod invite;
+    pub mod join_request;
+    pub mod managed;
+    pub mod missed_pick;
+    pub mod pick_revision;
     pub mod player_ranking;
+    pub mod profile;
+    pub mod projection;
+    pub mod recap;
     pub mod spread;
     pub mod team;
     pub mod user;
@@ -78,12 +98,20 @@ pub fn router() -> Router<AppStateRef> {
         .nest("/admin", site_admin_routes)
         .nest("/book", book::router())
         .route("/team-search", get(team::search::search))
+        .route("/join", post(invite::join_code))
+        .route(
+            "/join-request/{book_id}",
+            get(join_request::page).post(join_request::submit),
+        )
+        .route("/join-requests/{request_id}", delete(join_request::dismiss))
+        .route("/user/{user_id}", get(profile::handler))
         .route("/", get(home::handler))
         // ------------------^ Logged in Routes ^------------------
         .route_layer(axum_login::login_required!(
             BackendPgDB,
             login_url = "/login"
         ))
+        .route("/join/{code}", get(invite::join_link))
         .nest_service("/public", ServeDir::new("public"))
         .merge(session::router())
         .fallback(get((StatusCode::NOT_FOUND, "Could not find your route"))) // TODO: Add funny status page
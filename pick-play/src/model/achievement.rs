@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use sqlx::{types::Json, PgPool};
+
+use super::{
+    book::leaderboard,
+    recap::{Recap, RecapUser},
+};
+
+const HAT_TRICK_WINS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, sqlx::Type)]
+#[sqlx(type_name = "badges", rename_all = "snake_case")]
+pub enum Badge {
+    SeasonWin,
+    ChapterWin,
+    HatTrick,
+    PerfectWeek,
+    BonusTopScore,
+}
+
+impl Badge {
+    pub const ALL: [Badge; 5] = [
+        Badge::SeasonWin,
+        Badge::ChapterWin,
+        Badge::HatTrick,
+        Badge::PerfectWeek,
+        Badge::BonusTopScore,
+    ];
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Badge::SeasonWin => "🏆",
+            Badge::ChapterWin => "🥇",
+            Badge::HatTrick => "🎩",
+            Badge::PerfectWeek => "💯",
+            Badge::BonusTopScore => "⭐",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Badge::SeasonWin => "Season Win",
+            Badge::ChapterWin => "Chapter Win",
+            Badge::HatTrick => "Hat Trick",
+            Badge::PerfectWeek => "Perfect Week",
+            Badge::BonusTopScore => "Bonus Top Score",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Badge::SeasonWin => "Finished first in the book once every chapter was graded",
+            Badge::ChapterWin => "Finished first in a chapter",
+            Badge::HatTrick => "Won three chapters in a row",
+            Badge::PerfectWeek => "Got every pick in a chapter right",
+            Badge::BonusTopScore => "Top score on a chapter's bonus questions",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Earned {
+    pub user_id: i32,
+    pub chapter_id: i32,
+    pub badge: Badge,
+}
+
+fn members(users: &[RecapUser]) -> impl Iterator<Item = &RecapUser> {
+    users.iter().filter(|user| user.entry_id.is_none())
+}
+
+/// Works out every badge from the book's recaps, which must be in chapter
+/// order. A hat trick is awarded on the third straight win, and the streak
+/// starts over after it. Badges go to members, so extra entries don't earn
+/// any.
+pub fn evaluate(recaps: &[(i32, Recap)]) -> Vec<Earned> {
+    let mut earned = vec![];
+    let mut streaks: HashMap<i32, usize> = HashMap::new();
+
+    for (chapter_id, recap) in recaps {
+        let mut award = |user_id, badge| {
+            earned.push(Earned {
+                user_id,
+                chapter_id: *chapter_id,
+                badge,
+            })
+        };
+
+        members(&recap.perfect_cards).for_each(|user| award(user.user_id, Badge::PerfectWeek));
+        members(&recap.bonus_leaders).for_each(|user| award(user.user_id, Badge::BonusTopScore));
+
+        let winners = members(&recap.winners).collect::<Vec<_>>();
+        streaks.retain(|user_id, _| winners.iter().any(|user| user.user_id == *user_id));
+        for user in winners {
+            award(user.user_id, Badge::ChapterWin);
+
+            let streak = streaks.entry(user.user_id).or_default();
+            *streak += 1;
+            if *streak == HAT_TRICK_WINS {
+                award(user.user_id, Badge::HatTrick);
+                *streak = 0;
+            }
+        }
+    }
+
+    earned
+}
+
+/// Re-evaluates the book's badges from its stored recaps, replacing the
+/// previous results so that regrading can take badges away as well. Only
+/// chapters with every event graded count, and the season win goes to the
+/// book leaders once every chapter is closed and graded.
+pub async fn evaluate_achievements(book_id: i32, pool: &PgPool) -> Result<(), sqlx::Error> {
+    let chapters = sqlx::query!(
+        r#"
+        SELECT
+            c.id,
+            c.is_open,
+            EXISTS (SELECT 1 FROM events AS e WHERE e.chapter_id = c.id)
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM events AS e
+                    WHERE e.chapter_id = c.id
+                        AND CASE
+                            WHEN e.event_type = 'spread_group' THEN EXISTS (
+                                SELECT 1
+                                FROM JSONB_ARRAY_ELEMENTS(e.contents -> 'spread_group') AS s(spread)
+                                WHERE COALESCE(s.spread ->> 'answer', '') NOT IN ('home', 'away', 'push')
+                            )
+                            ELSE JSONB_TYPEOF(e.contents -> 'user_input' -> 'acceptable_answers') IS DISTINCT FROM 'array'
+                        END
+                ) AS "is_graded!"
+        FROM chapters AS c
+        WHERE c.book_id = $1
+        ORDER BY c.position
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let recaps = sqlx::query!(
+        r#"
+        SELECT r.chapter_id, r.contents AS "contents: Json<Recap>"
+        FROM chapter_recaps AS r
+        JOIN chapters AS c ON r.chapter_id = c.id
+        WHERE r.book_id = $1
+        ORDER BY c.position
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .filter(|row| {
+        chapters
+            .iter()
+            .any(|chapter| chapter.id == row.chapter_id && chapter.is_graded)
+    })
+    .map(|row| (row.chapter_id, row.contents.0))
+    .collect::<Vec<_>>();
+
+    let mut earned = evaluate(&recaps);
+
+    let season_over = chapters
+        .iter()
+        .all(|chapter| !chapter.is_open && chapter.is_graded);
+    if let (true, Some(final_chapter)) = (season_over, chapters.last()) {
+        earned.extend(
+            leaderboard(book_id, None, None, pool)
+                .await?
+                .into_iter()
+                // Guests are grouped under a placeholder id
+                .filter(|standing| {
+                    standing.rank == 1 && standing.user_id > 0 && standing.entry_id.is_none()
+                })
+                .map(|standing| Earned {
+                    user_id: standing.user_id,
+                    chapter_id: final_chapter.id,
+                    badge: Badge::SeasonWin,
+                }),
+        );
+    }
+
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM achievements WHERE book_id = $1", book_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    for Earned {
+        user_id,
+        chapter_id,
+        badge,
+    } in earned
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO achievements (book_id, user_id, chapter_id, badge)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING
+            "#,
+            book_id,
+            user_id,
+            chapter_id,
+            badge as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Achievement {
+    pub user_id: i32,
+    pub username: String,
+    pub chapter_id: i32,
+    pub chapter_title: String,
+    pub badge: Badge,
+}
+
+/// Badges earned by the book's members in chapters they can see
+pub async fn book_achievements(
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<Achievement>, sqlx::Error> {
+    sqlx::query_as!(
+        Achievement,
+        r#"
+        SELECT
+            a.user_id,
+            u.username,
+            a.chapter_id,
+            c.title AS chapter_title,
+            a.badge AS "badge: Badge"
+        FROM achievements AS a
+        JOIN users AS u ON a.user_id = u.id
+        JOIN chapters AS c ON a.chapter_id = c.id
+        WHERE a.book_id = $1 AND c.is_visible
+        ORDER BY u.username, a.badge, c.position
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// How many times each user has earned each badge
+pub fn badge_counts(achievements: &[Achievement]) -> HashMap<i32, Vec<(Badge, usize)>> {
+    let mut counts: HashMap<i32, Vec<(Badge, usize)>> = HashMap::new();
+    for achievement in achievements {
+        let badges = counts.entry(achievement.user_id).or_default();
+        match badges
+            .iter_mut()
+            .find(|(badge, _)| *badge == achievement.badge)
+        {
+            Some((_, count)) => *count += 1,
+            None => badges.push((achievement.badge, 1)),
+        }
+    }
+    counts
+        .values_mut()
+        .for_each(|badges| badges.sort_unstable());
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(user_id: i32) -> RecapUser {
+        RecapUser {
+            user_id,
+            entry_id: None,
+            username: format!("user{user_id}"),
+        }
+    }
+
+    fn won_by(winners: &[i32]) -> Recap {
+        Recap {
+            winners: winners.iter().copied().map(user).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn badges(earned: &[Earned], badge: Badge) -> Vec<(i32, i32)> {
+        earned
+            .iter()
+            .filter(|earned| earned.badge == badge)
+            .map(|earned| (earned.user_id, earned.chapter_id))
+            .collect()
+    }
+
+    #[test]
+    fn hat_trick_resets_after_three_wins() {
+        let recaps = (1..=7)
+            .map(|chapter_id| (chapter_id, won_by(&[1])))
+            .collect::<Vec<_>>();
+
+        let earned = evaluate(&recaps);
+        assert_eq!(badges(&earned, Badge::ChapterWin).len(), 7);
+        assert_eq!(badges(&earned, Badge::HatTrick), vec![(1, 3), (1, 6)]);
+    }
+
+    #[test]
+    fn chapter_without_a_winner_breaks_streaks() {
+        let recaps = vec![
+            (1, won_by(&[1, 2])),
+            (2, won_by(&[1, 2])),
+            (3, won_by(&[])),
+            (4, won_by(&[1])),
+            (5, won_by(&[2])),
+            (6, won_by(&[2])),
+        ];
+
+        let earned = evaluate(&recaps);
+        assert!(badges(&earned, Badge::HatTrick).is_empty());
+        assert_eq!(
+            badges(&earned, Badge::ChapterWin),
+            vec![(1, 1), (2, 1), (1, 2), (2, 2), (1, 4), (2, 5), (2, 6)]
+        );
+    }
+
+    #[test]
+    fn shared_wins_count_for_every_winner() {
+        let recaps = (1..=3)
+            .map(|chapter_id| (chapter_id, won_by(&[1, 2])))
+            .collect::<Vec<_>>();
+
+        let earned = evaluate(&recaps);
+        assert_eq!(badges(&earned, Badge::HatTrick), vec![(1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn extra_entries_earn_nothing() {
+        let entry = RecapUser {
+            entry_id: Some(1),
+            ..user(1)
+        };
+        let recaps = (1..=3)
+            .map(|chapter_id| {
+                (
+                    chapter_id,
+                    Recap {
+                        winners: vec![entry.clone()],
+                        perfect_cards: vec![entry.clone()],
+                        bonus_leaders: vec![entry.clone()],
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert!(evaluate(&recaps).is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use sqlx::PgPool;
 
 use crate::AppError;
@@ -10,9 +11,101 @@ pub enum BookRole {
     Admin,
     Participant,
     Guest { chapter_ids: Vec<i32> },
+    Moderator { capabilities: Vec<BookCapability> },
+    Spectator,
     Unauthorized,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookCapability {
+    Grade,
+    ManageChapters,
+    ManageMembers,
+    AdjustPoints,
+}
+
+impl BookCapability {
+    pub const ALL: [BookCapability; 4] = [
+        BookCapability::Grade,
+        BookCapability::ManageChapters,
+        BookCapability::ManageMembers,
+        BookCapability::AdjustPoints,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            BookCapability::Grade => "grade",
+            BookCapability::ManageChapters => "manage_chapters",
+            BookCapability::ManageMembers => "manage_members",
+            BookCapability::AdjustPoints => "adjust_points",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BookCapability::Grade => "Grade",
+            BookCapability::ManageChapters => "Chapters",
+            BookCapability::ManageMembers => "Members",
+            BookCapability::AdjustPoints => "Points",
+        }
+    }
+}
+
+impl BookRole {
+    /// Owners and admins hold every capability, and are the only roles that
+    /// can delete the book or hand out capabilities to other members
+    pub fn is_admin(&self) -> bool {
+        matches!(self, BookRole::Owner | BookRole::Admin)
+    }
+
+    pub fn has_capability(&self, capability: BookCapability) -> bool {
+        match self {
+            BookRole::Owner | BookRole::Admin => true,
+            BookRole::Moderator { capabilities } => capabilities.contains(&capability),
+            _ => false,
+        }
+    }
+
+    pub fn has_any_capability(&self) -> bool {
+        BookCapability::ALL
+            .iter()
+            .any(|capability| self.has_capability(*capability))
+    }
+
+    /// Grants or revokes a capability, returning `None` for roles that can't hold them
+    pub fn with_capability(&self, capability: BookCapability, enabled: bool) -> Option<BookRole> {
+        let mut capabilities = match self {
+            BookRole::Participant => Vec::new(),
+            BookRole::Moderator { capabilities } => capabilities.clone(),
+            _ => return None,
+        };
+
+        capabilities.retain(|c| *c != capability);
+        if enabled {
+            capabilities.push(capability);
+        }
+
+        if capabilities.is_empty() {
+            Some(BookRole::Participant)
+        } else {
+            Some(BookRole::Moderator { capabilities })
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BookRole::Owner => "owner",
+            BookRole::Admin => "admin",
+            BookRole::Participant => "participant",
+            BookRole::Guest { .. } => "guest",
+            BookRole::Moderator { .. } => "moderator",
+            BookRole::Spectator => "spectator",
+            BookRole::Unauthorized => "unauthorized",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct BookSubscription {
     pub id: i32,
@@ -84,6 +177,7 @@ pub struct BookSubscriptionStats {
     pub recent_chapter_id: Option<i32>,
     pub recent_chapter_title: Option<String>,
     pub recent_chapter_is_open: Option<bool>,
+    pub is_spectator: bool,
 }
 
 pub async fn user_books_stats(
@@ -97,11 +191,12 @@ pub async fn user_books_stats(
             SELECT
                 book_id,
                 user_id,
+                NULL::INT AS entry_id,
                 -- Calculate total points from picks/events
                 COALESCE((
                     SELECT SUM(p.points)
                     FROM picks p
-                    WHERE p.book_id = s.book_id AND p.user_id = s.user_id
+                    WHERE p.book_id = s.book_id AND p.user_id = s.user_id AND p.entry_id IS NULL
                 ), 0) +
                 -- Calculate total extra points
                 COALESCE((
@@ -110,11 +205,23 @@ pub async fn user_books_stats(
                     WHERE ap.book_id = s.book_id AND ap.user_id = s.user_id
                 ), 0) AS total_points
             FROM subscriptions s
+            WHERE NOT (s.role ? 'spectator')
+            UNION ALL
+            -- Extra entries rank alongside everyone else
+            SELECT
+                e.book_id,
+                e.user_id,
+                e.id,
+                COALESCE((SELECT SUM(p.points) FROM picks p WHERE p.entry_id = e.id), 0)
+            FROM book_entries e
+            JOIN subscriptions s ON e.user_id = s.user_id AND e.book_id = s.book_id
+            WHERE NOT (s.role ? 'spectator')
         ),
         user_rankings AS (
             SELECT
                 book_id,
                 user_id,
+                entry_id,
                 total_points,
                 RANK() OVER (PARTITION BY book_id ORDER BY total_points DESC) as user_rank
             FROM user_book_stats
@@ -122,15 +229,16 @@ pub async fn user_books_stats(
         SELECT
             b.id AS "id!",
             b.name AS "name!",
-            (SELECT COUNT(*) FROM subscriptions WHERE book_id = b.id AND not role ? 'guest')::INT AS "num_members!",
-            (SELECT c.id FROM chapters AS c WHERE c.book_id = b.id AND c.is_visible ORDER BY c.created_at DESC LIMIT 1) AS recent_chapter_id,
-            (SELECT c.title FROM chapters AS c WHERE c.book_id = b.id AND c.is_visible ORDER BY c.created_at DESC LIMIT 1) AS recent_chapter_title,
-            (SELECT c.is_open FROM chapters AS c WHERE c.book_id = b.id AND c.is_visible ORDER BY c.created_at DESC LIMIT 1) AS recent_chapter_is_open,
-            ur.total_points::INT AS "user_points!",
-            ur.user_rank::INT AS "rank!"
+            (SELECT COUNT(*) FROM subscriptions WHERE book_id = b.id AND not role ? 'guest' AND not role ? 'spectator')::INT AS "num_members!",
+            (SELECT c.id FROM chapters AS c WHERE c.book_id = b.id AND c.is_visible ORDER BY c.position DESC LIMIT 1) AS recent_chapter_id,
+            (SELECT c.title FROM chapters AS c WHERE c.book_id = b.id AND c.is_visible ORDER BY c.position DESC LIMIT 1) AS recent_chapter_title,
+            (SELECT c.is_open FROM chapters AS c WHERE c.book_id = b.id AND c.is_visible ORDER BY c.position DESC LIMIT 1) AS recent_chapter_is_open,
+            COALESCE(ur.total_points, 0)::INT AS "user_points!",
+            COALESCE(ur.user_rank, 0)::INT AS "rank!",
+            s.role ? 'spectator' AS "is_spectator!"
         FROM subscriptions AS s
         JOIN books AS b ON s.book_id = b.id
-        LEFT JOIN user_rankings ur ON ur.book_id = b.id AND ur.user_id = s.user_id
+        LEFT JOIN user_rankings ur ON ur.book_id = b.id AND ur.user_id = s.user_id AND ur.entry_id IS NULL
         WHERE s.user_id = $1
         ORDER BY b.created_at DESC;
         "#,
@@ -160,11 +268,12 @@ pub async fn book_rank(
           -- Points from picks/events
           SELECT
             p.user_id,
+            p.entry_id,
             p.book_id,
             COALESCE(SUM(p.points), 0) AS event_points
           FROM picks p
           WHERE p.book_id = $2  -- Replace $1 with the specific book_id
-          GROUP BY p.user_id, p.book_id
+          GROUP BY p.user_id, p.entry_id, p.book_id
         ),
         user_added_points AS (
           -- Extra/added points
@@ -180,13 +289,19 @@ pub async fn book_rank(
           -- Calculate rankings for ALL users first
           SELECT
             s.user_id,
-            s.book_id,
+            e.id AS entry_id,
             u.username,
-            COALESCE(uep.event_points, 0) + COALESCE(uap.extra_points, 0) AS total_points,
-            RANK() OVER (ORDER BY (COALESCE(uep.event_points, 0) + COALESCE(uap.extra_points, 0)) DESC) as ranking
+            COALESCE(uep.event_points, 0) + CASE WHEN e.id IS NULL THEN COALESCE(uap.extra_points, 0) ELSE 0 END AS total_points,
+            RANK() OVER (ORDER BY (COALESCE(uep.event_points, 0) + CASE WHEN e.id IS NULL THEN COALESCE(uap.extra_points, 0) ELSE 0 END) DESC) as ranking
           FROM subscriptions s
           JOIN users u ON s.user_id = u.id
-          LEFT JOIN user_event_points uep ON s.user_id = uep.user_id AND s.book_id = uep.book_id
+          -- One row for the member's own entry and one for each extra entry
+          LEFT JOIN LATERAL (
+            SELECT NULL::INT AS id
+            UNION ALL
+            SELECT book_entries.id FROM book_entries WHERE book_entries.book_id = s.book_id AND book_entries.user_id = s.user_id
+          ) e ON TRUE
+          LEFT JOIN user_event_points uep ON s.user_id = uep.user_id AND e.id IS NOT DISTINCT FROM uep.entry_id AND s.book_id = uep.book_id
           LEFT JOIN user_added_points uap ON s.user_id = uap.user_id AND s.book_id = uap.book_id
           WHERE s.book_id = $2  -- Replace $1 with the specific book_id
         )
@@ -197,7 +312,7 @@ pub async fn book_rank(
           total_points::INT AS "points!",
           ranking::INT AS "rank!"
         FROM user_rankings
-        WHERE user_id = $1  -- Replace $2 with the specific user_id
+        WHERE user_id = $1 AND entry_id IS NULL  -- Replace $2 with the specific user_id
         ORDER BY ranking;
         "#,
         user_id,
@@ -210,6 +325,8 @@ pub async fn book_rank(
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct BookRankingStats {
     pub user_id: i32,
+    /// Set when the row is one of the member's extra entries
+    pub entry_id: Option<i32>,
     pub username: String,
     pub earned_points: i32,
     pub added_points: i32,
@@ -217,54 +334,97 @@ pub struct BookRankingStats {
     pub rank: i32,
 }
 
+/// `as_of` limits the standings to points earned up to and including that
+/// chapter. Extra entries get a row of their own, named after their owner.
 pub async fn leaderboard(
     book_id: i32,
+    group: Option<&str>,
+    as_of: Option<i32>,
     pool: &PgPool,
 ) -> Result<Vec<BookRankingStats>, sqlx::Error> {
     sqlx::query_as!(
         BookRankingStats,
         r#"
-        WITH earned_points AS (
+        WITH cutoff AS (
+            SELECT position
+            FROM chapters
+            WHERE id = $3 AND book_id = $1
+        ),
+        earned_points AS (
             SELECT
-                user_id,
-                COALESCE(SUM(points), 0) AS points
+                picks.user_id,
+                picks.entry_id,
+                COALESCE(SUM(picks.points), 0) AS points
             FROM picks
-            WHERE book_id = $1
-            GROUP BY user_id
+            JOIN chapters ON picks.chapter_id = chapters.id
+            WHERE picks.book_id = $1
+                AND ($3::INT IS NULL OR chapters.position <= (SELECT position FROM cutoff))
+            GROUP BY picks.user_id, picks.entry_id
         ),
         added_points AS (
             SELECT
-                user_id,
-                COALESCE(SUM(points), 0) AS points
+                added_points.user_id,
+                COALESCE(SUM(added_points.points), 0) AS points
             FROM added_points
-            WHERE book_id = $1
-            GROUP BY user_id
+            LEFT JOIN chapters ON added_points.chapter_id = chapters.id
+            WHERE added_points.book_id = $1
+                AND (
+                    $3::INT IS NULL
+                    OR added_points.chapter_id IS NULL
+                    OR chapters.position <= (SELECT position FROM cutoff)
+                )
+            GROUP BY added_points.user_id
+        ),
+        entries AS (
+            SELECT subscriptions.user_id, NULL::INT AS entry_id, users.username
+            FROM subscriptions
+            JOIN users ON subscriptions.user_id = users.id
+            WHERE subscriptions.book_id = $1
+            UNION ALL
+            SELECT book_entries.user_id, book_entries.id, users.username || ' (' || book_entries.name || ')'
+            FROM book_entries
+            JOIN users ON book_entries.user_id = users.id
+            WHERE book_entries.book_id = $1
         ),
         regular_users AS (
             SELECT
                 users.id AS user_id,
-                users.username,
+                entries.entry_id,
+                entries.username,
                 COALESCE(earned_points.points, 0) AS earned_points,
                 COALESCE(added_points.points, 0) AS added_points,
                 COALESCE(earned_points.points, 0) + COALESCE(added_points.points, 0) AS total_points
             FROM subscriptions
             JOIN users ON subscriptions.user_id = users.id
+            JOIN entries ON users.id = entries.user_id
             LEFT JOIN earned_points ON users.id = earned_points.user_id
-            LEFT JOIN added_points ON users.id = added_points.user_id
-            WHERE subscriptions.book_id = $1 AND NOT (subscriptions.role ? 'guest')
+                AND entries.entry_id IS NOT DISTINCT FROM earned_points.entry_id
+            -- Points are added to members, not to their extra entries
+            LEFT JOIN added_points ON users.id = added_points.user_id AND entries.entry_id IS NULL
+            WHERE subscriptions.book_id = $1
+                AND NOT (subscriptions.role ? 'guest')
+                AND NOT (subscriptions.role ? 'spectator')
+                AND ($2::TEXT IS NULL OR EXISTS (
+                    SELECT 1
+                    FROM subscription_groups
+                    WHERE subscription_groups.book_id = $1
+                        AND subscription_groups.user_id = users.id
+                        AND subscription_groups.name = $2
+                ))
         ),
         guest_users AS (
             SELECT
                 -1 AS user_id,  -- Special ID for guests group
+                NULL::INT AS entry_id,
                 'Guests' AS username,
                 COALESCE(SUM(earned_points.points), 0) AS earned_points,
                 COALESCE(SUM(added_points.points), 0) AS added_points,
                 COALESCE(SUM(earned_points.points), 0) + COALESCE(SUM(added_points.points), 0) AS total_points
             FROM subscriptions
             JOIN users ON subscriptions.user_id = users.id
-            LEFT JOIN earned_points ON users.id = earned_points.user_id
+            LEFT JOIN earned_points ON users.id = earned_points.user_id AND earned_points.entry_id IS NULL
             LEFT JOIN added_points ON users.id = added_points.user_id
-            WHERE subscriptions.book_id = $1 AND (subscriptions.role ? 'guest')
+            WHERE subscriptions.book_id = $1 AND (subscriptions.role ? 'guest') AND $2::TEXT IS NULL
         ),
         combined AS (
             SELECT * FROM regular_users
@@ -274,6 +434,7 @@ pub async fn leaderboard(
         )
         SELECT
             user_id AS "user_id!",
+            entry_id AS "entry_id?",
             username AS "username!",
             earned_points::INT AS "earned_points!",
             added_points::INT AS "added_points!",
@@ -282,15 +443,89 @@ pub async fn leaderboard(
         FROM combined
         ORDER BY total_points DESC;
         "#,
-        book_id
+        book_id,
+        group,
+        as_of
     ).fetch_all(pool).await
 }
 
+#[derive(Debug, Clone)]
+pub struct Standing {
+    pub user_id: i32,
+    /// Set when the standing is for one of the member's extra entries
+    pub entry_id: Option<i32>,
+    pub username: String,
+    pub chapter_id: i32,
+    pub chapter_title: String,
+    pub total_points: i32,
+    pub rank: i32,
+}
+
+/// Every member's cumulative points and rank after each graded chapter,
+/// ordered by chapter
+pub async fn standings_history(book_id: i32, pool: &PgPool) -> Result<Vec<Standing>, sqlx::Error> {
+    sqlx::query_as!(
+        Standing,
+        r#"
+        WITH chapter_points AS (
+            SELECT
+                c.id AS chapter_id,
+                c.title,
+                c.position,
+                u.id AS user_id,
+                e.id AS entry_id,
+                COALESCE(u.username || ' (' || e.name || ')', u.username) AS username,
+                COALESCE(SUM(p.points), 0) AS points
+            FROM chapters AS c
+            JOIN subscriptions AS s ON s.book_id = c.book_id
+            JOIN users AS u ON s.user_id = u.id
+            -- One row for the member's own entry and one for each extra entry
+            JOIN LATERAL (
+                SELECT NULL::INT AS id, NULL::TEXT AS name
+                UNION ALL
+                SELECT book_entries.id, book_entries.name
+                FROM book_entries
+                WHERE book_entries.book_id = c.book_id AND book_entries.user_id = u.id
+            ) AS e ON TRUE
+            LEFT JOIN picks AS p ON p.chapter_id = c.id AND p.user_id = u.id AND p.entry_id IS NOT DISTINCT FROM e.id
+            WHERE c.book_id = $1
+                AND NOT (s.role ? 'guest')
+                AND NOT (s.role ? 'spectator')
+                AND EXISTS (
+                    SELECT 1
+                    FROM picks AS graded
+                    WHERE graded.chapter_id = c.id AND graded.points IS NOT NULL
+                )
+            GROUP BY c.id, c.title, c.position, u.id, u.username, e.id, e.name
+        ),
+        cumulative AS (
+            SELECT
+                *,
+                SUM(points) OVER (PARTITION BY user_id, entry_id ORDER BY position) AS total_points
+            FROM chapter_points
+        )
+        SELECT
+            user_id AS "user_id!",
+            entry_id AS "entry_id?",
+            username AS "username!",
+            chapter_id AS "chapter_id!",
+            title AS "chapter_title!",
+            total_points::INT AS "total_points!",
+            RANK() OVER (PARTITION BY chapter_id ORDER BY total_points DESC)::INT AS "rank!"
+        FROM cumulative
+        ORDER BY position, total_points DESC, username
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct BookMember {
     pub id: i32,
     pub username: String,
-    pub role: serde_json::Value,
+    pub role: Json<BookRole>,
 }
 
 pub async fn get_book_members(
@@ -301,7 +536,7 @@ pub async fn get_book_members(
     sqlx::query_as!(
         BookMember,
         r#"
-        SELECT u.id, u.username, s.role
+        SELECT u.id, u.username, s.role AS "role: Json<BookRole>"
         FROM users AS u
         JOIN subscriptions AS s ON u.id=s.user_id
         JOIN books AS b on s.book_id=b.id
@@ -348,7 +583,7 @@ pub async fn search_users_not_in_book(
 pub async fn add_user_to_book(
     user_id: i32,
     book_id: i32,
-    pool: &PgPool,
+    executor: impl sqlx::PgExecutor<'_>,
 ) -> Result<Option<i32>, sqlx::Error> {
     sqlx::query!(
         r#"
@@ -361,7 +596,7 @@ pub async fn add_user_to_book(
         user_id,
         book_id
     )
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await
     .map(|row| row.map(|r| r.user_id))
 }
@@ -371,6 +606,19 @@ pub async fn remove_user_from_book(
     book_id: i32,
     pool: &PgPool,
 ) -> Result<(), sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM subscription_groups
+        WHERE user_id = $1 AND book_id = $2
+        "#,
+        user_id,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
     sqlx::query!(
         r#"
         DELETE FROM subscriptions
@@ -379,6 +627,52 @@ pub async fn remove_user_from_book(
         user_id,
         book_id
     )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await
+}
+
+pub async fn set_member_role(
+    user_id: i32,
+    book_id: i32,
+    role: &BookRole,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE subscriptions
+        SET role = $3
+        WHERE user_id = $1 AND book_id = $2
+        "#,
+        user_id,
+        book_id,
+        Json(role) as _
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn add_points(
+    user_id: i32,
+    book_id: i32,
+    points: i32,
+    reason: &str,
+    chapter_id: Option<i32>,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO added_points (user_id, book_id, points, reason, chapter_id)
+        VALUES ($1, $2, $3, $4, (SELECT id FROM chapters WHERE id = $5 AND book_id = $2))
+        "#,
+        user_id,
+        book_id,
+        points,
+        reason,
+        chapter_id
+    )
     .execute(pool)
     .await
     .map(|_| ())
@@ -387,6 +681,40 @@ pub async fn remove_user_from_book(
 pub async fn delete_book_cascade(book_id: i32, pool: &PgPool) -> Result<(), sqlx::Error> {
     let mut transaction = pool.begin().await?;
 
+    sqlx::query!(
+        r#"
+        DELETE FROM deadline_extensions
+        WHERE chapter_id IN (SELECT id FROM chapters WHERE book_id = $1)
+        "#,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM missed_picks
+        WHERE chapter_id IN (SELECT id FROM chapters WHERE book_id = $1)
+        "#,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM pick_drafts
+        WHERE chapter_id IN (SELECT id FROM chapters WHERE book_id = $1)
+        "#,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(r#"DELETE FROM pick_revisions WHERE book_id = $1"#, book_id)
+        .execute(&mut *transaction)
+        .await?;
+
     sqlx::query!(r#"DELETE FROM picks WHERE book_id = $1"#, book_id)
         .execute(&mut *transaction)
         .await?;
@@ -395,10 +723,65 @@ pub async fn delete_book_cascade(book_id: i32, pool: &PgPool) -> Result<(), sqlx
         .execute(&mut *transaction)
         .await?;
 
+    sqlx::query!(r#"DELETE FROM book_invites WHERE book_id = $1"#, book_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query!(r#"DELETE FROM join_requests WHERE book_id = $1"#, book_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query!(r#"DELETE FROM added_points WHERE book_id = $1"#, book_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query!(r#"DELETE FROM achievements WHERE book_id = $1"#, book_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM pick_commitments
+        WHERE chapter_id IN (SELECT id FROM chapters WHERE book_id = $1)
+        "#,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM chapter_commitments
+        WHERE chapter_id IN (SELECT id FROM chapters WHERE book_id = $1)
+        "#,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(r#"DELETE FROM grading_changes WHERE book_id = $1"#, book_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query!(r#"DELETE FROM chapter_recaps WHERE book_id = $1"#, book_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query!(r#"DELETE FROM book_entries WHERE book_id = $1"#, book_id)
+        .execute(&mut *transaction)
+        .await?;
+
     sqlx::query!(r#"DELETE FROM chapters WHERE book_id = $1"#, book_id)
         .execute(&mut *transaction)
         .await?;
 
+    sqlx::query!(
+        r#"DELETE FROM subscription_groups WHERE book_id = $1"#,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
     sqlx::query!(r#"DELETE FROM subscriptions WHERE book_id = $1"#, book_id)
         .execute(&mut *transaction)
         .await?;
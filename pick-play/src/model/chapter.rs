@@ -2,6 +2,8 @@ use sqlx::PgPool;
 
 use crate::AppError;
 
+use super::missed_pick::MissedPickPolicy;
+
 #[derive(Debug, Clone)]
 pub struct Chapter {
     pub chapter_id: i32,
@@ -17,7 +19,7 @@ pub async fn get_chapters(book_id: i32, pool: &PgPool) -> Result<Vec<Chapter>, s
         r#"	SELECT id AS chapter_id, book_id, is_open, title, is_visible
 			FROM chapters
 			WHERE book_id = $1
-            ORDER BY created_at DESC
+            ORDER BY position DESC
 		"#,
         book_id
     )
@@ -38,13 +40,118 @@ pub async fn get_chapter(chapter_id: i32, pool: &PgPool) -> Result<Chapter, sqlx
     .await
 }
 
+/// The chapter's place in its book counting from 1, and how many chapters
+/// the book has
+pub async fn chapter_order(
+    chapter_id: i32,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<(i64, i64), sqlx::Error> {
+    let order = sqlx::query!(
+        r#"
+        SELECT ordered.place AS "place!", ordered.total AS "total!"
+        FROM (
+            SELECT
+                id,
+                ROW_NUMBER() OVER (ORDER BY position, id) AS place,
+                COUNT(*) OVER () AS total
+            FROM chapters
+            WHERE book_id = $2
+        ) AS ordered
+        WHERE ordered.id = $1
+        "#,
+        chapter_id,
+        book_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok((order.place, order.total))
+}
+
+/// Swaps the chapter with the one before or after it. Does nothing when the
+/// chapter is already first or last.
+pub async fn move_chapter(
+    chapter_id: i32,
+    book_id: i32,
+    earlier: bool,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        WITH target AS (
+            SELECT id, position FROM chapters WHERE id = $1 AND book_id = $2
+        ),
+        neighbor AS (
+            SELECT c.id, c.position
+            FROM chapters AS c, target AS t
+            WHERE c.book_id = $2
+                AND c.id <> t.id
+                AND CASE WHEN $3 THEN c.position < t.position ELSE c.position > t.position END
+            ORDER BY CASE WHEN $3 THEN -c.position ELSE c.position END
+            LIMIT 1
+        )
+        UPDATE chapters AS c
+        SET position = CASE WHEN c.id = t.id THEN n.position ELSE t.position END
+        FROM target AS t, neighbor AS n
+        WHERE c.id IN (t.id, n.id)
+        "#,
+        chapter_id,
+        book_id,
+        earlier
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+#[derive(Debug, Clone)]
+pub struct GradedChapter {
+    pub id: i32,
+    pub title: String,
+}
+
+/// Chapters with at least one graded pick, in chapter order
+pub async fn graded_chapters(
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<GradedChapter>, sqlx::Error> {
+    sqlx::query_as!(
+        GradedChapter,
+        r#"
+        SELECT c.id, c.title
+        FROM chapters AS c
+        WHERE c.book_id = $1
+            AND EXISTS (
+                SELECT 1
+                FROM picks
+                WHERE picks.chapter_id = c.id AND picks.points IS NOT NULL
+            )
+        ORDER BY c.position
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
 pub struct ChapterUser {
     pub user_id: i32,
+    /// Set when the row is one of the member's extra entries
+    pub entry_id: Option<i32>,
     pub username: String,
     pub total_points: i32,
     pub rank: i32,
+    /// Admin that last entered picks on the user's behalf, if any are still
+    /// standing
+    pub entered_by: Option<String>,
+    pub entered_at: Option<String>,
+    /// How the user's missed picks were filled in
+    pub missed_policy: Option<MissedPickPolicy>,
 }
 
+/// Everyone playing the chapter with their points, one row for each entry.
+/// Extra entries are named after their owner.
 pub async fn get_chapter_users(
     book_id: i32,
     chapter_id: i32,
@@ -54,31 +161,60 @@ pub async fn get_chapter_users(
         ChapterUser,
         r#"
         SELECT
-            user_id,
-            username,
-            COALESCE(total_points, 0)::INT as "total_points!",
-            RANK() OVER (ORDER BY total_points DESC)::INT as "rank!"
+            sub3.user_id AS "user_id!",
+            sub3.entry_id AS "entry_id?",
+            sub3.username AS "username!",
+            COALESCE(sub3.total_points, 0)::INT as "total_points!",
+            RANK() OVER (ORDER BY sub3.total_points DESC)::INT as "rank!",
+            proxy.proxy_username AS "entered_by?",
+            TO_CHAR(proxy.entered_at, 'YYYY-MM-DD HH24:MI') AS "entered_at?",
+            missed_picks.policy AS "missed_policy?: MissedPickPolicy"
         FROM (
             SELECT
                 sub1.id AS user_id,
+                sub1.entry_id,
                 sub1.USERNAME,
                 SUM(COALESCE(sub2.POINTS, 0)) AS TOTAL_POINTS
             FROM (
-                SELECT users.id, users.username
+                SELECT users.id, NULL::INT AS entry_id, users.username
                 FROM users
                 JOIN subscriptions on users.id = subscriptions.user_id
-                WHERE book_id = $1 AND COALESCE(((subscriptions.role->'guest'->'chapter_ids') @> to_jsonb($2::INT)), true)
+                WHERE book_id = $1
+                    AND NOT (subscriptions.role ? 'spectator')
+                    AND COALESCE(((subscriptions.role->'guest'->'chapter_ids') @> to_jsonb($2::INT)), true)
+                UNION ALL
+                SELECT users.id, book_entries.id, users.username || ' (' || book_entries.name || ')'
+                FROM book_entries
+                JOIN users ON book_entries.user_id = users.id
+                JOIN subscriptions ON subscriptions.user_id = users.id AND subscriptions.book_id = book_entries.book_id
+                WHERE book_entries.book_id = $1
+                    AND NOT (subscriptions.role ? 'spectator')
+                    AND NOT (subscriptions.role ? 'guest')
             ) as sub1
             LEFT JOIN (
-                SELECT picks.user_id, picks.points
+                SELECT picks.user_id, picks.entry_id, picks.points
                 FROM picks
                 WHERE picks.chapter_id = $2
-            ) as sub2 on sub1.id = sub2.user_id
+            ) as sub2 on sub1.id = sub2.user_id AND sub1.entry_id IS NOT DISTINCT FROM sub2.entry_id
             GROUP BY
                 sub1.ID,
+                sub1.entry_id,
                 sub1.USERNAME
         ) AS sub3
-        ORDER BY total_points DESC, username
+        LEFT JOIN LATERAL (
+            SELECT users.username AS proxy_username, picks.entered_at
+            FROM picks
+            JOIN users ON picks.entered_by = users.id
+            WHERE picks.chapter_id = $2
+                AND picks.user_id = sub3.user_id
+                AND picks.entry_id IS NOT DISTINCT FROM sub3.entry_id
+            ORDER BY picks.entered_at DESC
+            LIMIT 1
+        ) AS proxy ON TRUE
+        LEFT JOIN missed_picks ON missed_picks.chapter_id = $2
+            AND missed_picks.user_id = sub3.user_id
+            AND sub3.entry_id IS NULL
+        ORDER BY sub3.total_points DESC, sub3.username
         "#,
         book_id,
         chapter_id
@@ -88,6 +224,17 @@ pub async fn get_chapter_users(
     .map_err(AppError::from)
 }
 
+/// [`get_chapter_users`] without the extra entries, for choosing a member
+pub async fn get_chapter_members(
+    book_id: i32,
+    chapter_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<ChapterUser>, AppError<'_>> {
+    let mut users = get_chapter_users(book_id, chapter_id, pool).await?;
+    users.retain(|user| user.entry_id.is_none());
+    Ok(users)
+}
+
 pub struct ChapterStats {
     pub id: i32,
     pub title: String,
@@ -124,21 +271,21 @@ pub async fn chapters_with_stats(
             COALESCE((
                 SELECT COALESCE(SUM(points)::INT, 0)
                 FROM picks
-                WHERE user_id = $1 AND chapter_id = c.id
+                WHERE user_id = $1 AND entry_id IS NULL AND chapter_id = c.id
             ), 0) AS "user_points!",
             COALESCE((
                 SELECT COALESCE(rank, 0)::INT
                 FROM (
-                    SELECT user_id, RANK() OVER (ORDER BY SUM(points) DESC) as rank
+                    SELECT user_id, entry_id, RANK() OVER (ORDER BY SUM(points) DESC) as rank
                     FROM picks
                     WHERE chapter_id = c.id
-                    GROUP BY user_id
+                    GROUP BY user_id, entry_id
                 ) ranked_users
-                WHERE user_id = $1
+                WHERE user_id = $1 AND entry_id IS NULL
             ), 1) AS "user_rank!"
         FROM chapters AS c
         WHERE book_id = $2
-        ORDER BY c.created_at DESC
+        ORDER BY c.position DESC
     "#,
         user_id,
         book_id
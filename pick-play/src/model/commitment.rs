@@ -0,0 +1,192 @@
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Transaction};
+
+/// Hashes every entry's picks for the chapter and a root over those hashes.
+/// Each entry's picks are serialized as JSONB text, whose key order and
+/// spacing Postgres always writes the same way, so the hash can be recomputed
+/// from the stored picks at any time. Extra entries add their `entry_id` to
+/// the payload. The root is the SHA-256 of the entries' hex hashes sorted and
+/// joined together.
+///
+/// Commitments are never replaced. Relocking the chapter or a member
+/// submitting late under an extension (`late_user_id`) adds a new one, so
+/// every root that was ever published stays on record.
+pub async fn commit_picks(
+    chapter_id: i32,
+    late_user_id: Option<i32>,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        WITH payloads AS (
+            SELECT
+                user_id,
+                entry_id,
+                (
+                    JSONB_BUILD_OBJECT(
+                        'chapter_id', chapter_id,
+                        'user_id', user_id,
+                        'picks', JSONB_AGG(
+                            JSONB_BUILD_OBJECT('event_id', event_id, 'choice', choice, 'wager', wager)
+                            ORDER BY event_id
+                        )
+                    )
+                    || CASE WHEN entry_id IS NULL THEN '{}'::JSONB ELSE JSONB_BUILD_OBJECT('entry_id', entry_id) END
+                )::TEXT AS payload
+            FROM picks
+            WHERE chapter_id = $1
+            GROUP BY chapter_id, user_id, entry_id
+        ),
+        hashes AS (
+            SELECT user_id, entry_id, payload, ENCODE(SHA256(CONVERT_TO(payload, 'UTF8')), 'hex') AS hash
+            FROM payloads
+        ),
+        commitment AS (
+            INSERT INTO chapter_commitments (chapter_id, root, late_user_id)
+            SELECT $1, ENCODE(SHA256(CONVERT_TO(COALESCE(STRING_AGG(hash, '' ORDER BY hash), ''), 'UTF8')), 'hex'), $2
+            FROM hashes
+            RETURNING id
+        )
+        INSERT INTO pick_commitments (commitment_id, chapter_id, user_id, entry_id, payload, hash)
+        SELECT commitment.id, $1, hashes.user_id, hashes.entry_id, hashes.payload, hashes.hash
+        FROM hashes, commitment
+        "#,
+        chapter_id,
+        late_user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .map(|_| ())
+}
+
+#[derive(Debug, Clone)]
+pub struct ChapterCommitment {
+    pub root: String,
+    pub committed_at: String,
+    /// Set when the commitment was made for a late submission
+    pub late_username: Option<String>,
+}
+
+/// Every commitment made for the chapter, oldest first
+pub async fn get_commitments(
+    chapter_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<ChapterCommitment>, sqlx::Error> {
+    sqlx::query_as!(
+        ChapterCommitment,
+        r#"
+        SELECT
+            c.root,
+            TO_CHAR(c.created_at, 'YYYY-MM-DD HH24:MI:SS') AS "committed_at!",
+            u.username AS "late_username?"
+        FROM chapter_commitments AS c
+        LEFT JOIN users AS u ON c.late_user_id = u.id
+        WHERE c.chapter_id = $1
+        ORDER BY c.id
+        "#,
+        chapter_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Whether the user's own picks still hash to what the latest commitment
+/// holds for them, `None` if nothing was committed for them
+pub async fn verify_picks(
+    chapter_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<Option<bool>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT c.payload::JSONB = JSONB_BUILD_OBJECT(
+            'chapter_id', c.chapter_id,
+            'user_id', c.user_id,
+            'picks', (
+                SELECT JSONB_AGG(
+                    JSONB_BUILD_OBJECT('event_id', p.event_id, 'choice', p.choice, 'wager', p.wager)
+                    ORDER BY p.event_id
+                )
+                FROM picks AS p
+                WHERE p.chapter_id = c.chapter_id AND p.user_id = c.user_id AND p.entry_id IS NULL
+            )
+        ) AS "matches!"
+        FROM pick_commitments AS c
+        WHERE c.chapter_id = $1 AND c.user_id = $2 AND c.entry_id IS NULL
+        ORDER BY c.commitment_id DESC
+        LIMIT 1
+        "#,
+        chapter_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Everything a member needs to check their picks against the published root
+/// without trusting the site
+#[derive(Debug, Clone, Serialize)]
+pub struct Receipt {
+    pub chapter_id: i32,
+    pub user_id: i32,
+    pub committed_at: String,
+    /// Exactly the text that was hashed
+    pub payload: String,
+    pub hash: String,
+    /// Every entry's hash, sorted
+    pub hashes: Vec<String>,
+    pub root: String,
+    pub verification: &'static str,
+}
+
+/// Receipt for the latest commitment that holds the user's own picks
+pub async fn get_receipt(
+    chapter_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<Option<Receipt>, sqlx::Error> {
+    let Some(own) = sqlx::query!(
+        r#"
+        SELECT
+            p.commitment_id,
+            p.payload,
+            p.hash,
+            c.root,
+            TO_CHAR(c.created_at, 'YYYY-MM-DD HH24:MI:SS') AS "committed_at!"
+        FROM pick_commitments AS p
+        JOIN chapter_commitments AS c ON p.commitment_id = c.id
+        WHERE p.chapter_id = $1 AND p.user_id = $2 AND p.entry_id IS NULL
+        ORDER BY p.commitment_id DESC
+        LIMIT 1
+        "#,
+        chapter_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+    let hashes = sqlx::query_scalar!(
+        r#"
+        SELECT hash
+        FROM pick_commitments
+        WHERE commitment_id = $1
+        ORDER BY hash
+        "#,
+        own.commitment_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Some(Receipt {
+        chapter_id,
+        user_id,
+        committed_at: own.committed_at,
+        payload: own.payload,
+        hash: own.hash,
+        hashes,
+        root: own.root,
+        verification: "sha256(payload) == hash, hash is in hashes, and sha256(hashes joined without separators) == root",
+    }))
+}
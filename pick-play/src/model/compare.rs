@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use super::event::{ChapterPick, ChapterPickHash, Event, EventContent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winner {
+    First,
+    Second,
+    Neither,
+}
+
+#[derive(Debug, Clone)]
+pub enum Matchup {
+    Spread {
+        home_id: i32,
+        away_id: i32,
+        home_spread: f64,
+    },
+    Question {
+        title: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Disagreement {
+    pub chapter_id: i32,
+    pub matchup: Matchup,
+    pub first_choice: String,
+    pub second_choice: String,
+    /// `None` until the game or question has been graded
+    pub winner: Option<Winner>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChapterDifferential {
+    pub chapter_id: i32,
+    pub first_points: i32,
+    pub second_points: i32,
+    /// First minus second, summed over this and every earlier chapter
+    pub running_differential: i32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HeadToHead {
+    pub disagreements: Vec<Disagreement>,
+    pub chapters: Vec<ChapterDifferential>,
+    pub agreements: usize,
+    pub compared: usize,
+}
+
+impl HeadToHead {
+    pub fn agreement_rate(&self) -> Option<f64> {
+        (self.compared > 0).then(|| self.agreements as f64 / self.compared as f64 * 100.0)
+    }
+}
+
+fn winner(first_correct: Option<bool>, second_correct: Option<bool>) -> Option<Winner> {
+    match (first_correct?, second_correct?) {
+        (true, false) => Some(Winner::First),
+        (false, true) => Some(Winner::Second),
+        _ => Some(Winner::Neither),
+    }
+}
+
+/// Compares two users over `events`, which must be in chapter order. Only
+/// picks that both users made count towards the agreement rate.
+pub fn head_to_head(
+    first_id: i32,
+    second_id: i32,
+    events: &[Event],
+    picks: &HashMap<ChapterPickHash, ChapterPick>,
+) -> HeadToHead {
+    let mut result = HeadToHead::default();
+
+    for event in events {
+        let first = picks.get(&ChapterPickHash {
+            event_id: event.id,
+            user_id: first_id,
+            entry_id: None,
+        });
+        let second = picks.get(&ChapterPickHash {
+            event_id: event.id,
+            user_id: second_id,
+            entry_id: None,
+        });
+
+        let points = |pick: Option<&ChapterPick>| match pick {
+            Some(
+                ChapterPick::SpreadGroup { points, .. } | ChapterPick::UserInput { points, .. },
+            ) => points.unwrap_or(0),
+            None => 0,
+        };
+        let (first_points, second_points) = (points(first), points(second));
+
+        match result.chapters.last_mut() {
+            Some(chapter) if chapter.chapter_id == event.chapter_id => {
+                chapter.first_points += first_points;
+                chapter.second_points += second_points;
+                chapter.running_differential += first_points - second_points;
+            }
+            previous => {
+                let running_differential =
+                    previous.map_or(0, |chapter| chapter.running_differential);
+                result.chapters.push(ChapterDifferential {
+                    chapter_id: event.chapter_id,
+                    first_points,
+                    second_points,
+                    running_differential: running_differential + first_points - second_points,
+                });
+            }
+        }
+
+        match (&event.contents.0, first, second) {
+            (
+                EventContent::SpreadGroup(spreads),
+                Some(ChapterPick::SpreadGroup {
+                    choice: first_choices,
+                    ..
+                }),
+                Some(ChapterPick::SpreadGroup {
+                    choice: second_choices,
+                    ..
+                }),
+            ) => {
+                for ((spread, first_choice), second_choice) in
+                    spreads.iter().zip(first_choices).zip(second_choices)
+                {
+                    result.compared += 1;
+                    if first_choice == second_choice {
+                        result.agreements += 1;
+                        continue;
+                    }
+
+                    let correct = |choice: &String| spread.answer.as_ref().map(|a| a == choice);
+                    result.disagreements.push(Disagreement {
+                        chapter_id: event.chapter_id,
+                        matchup: Matchup::Spread {
+                            home_id: spread.home_id,
+                            away_id: spread.away_id,
+                            home_spread: spread.home_spread,
+                        },
+                        first_choice: first_choice.clone(),
+                        second_choice: second_choice.clone(),
+                        winner: winner(correct(first_choice), correct(second_choice)),
+                    });
+                }
+            }
+            (
+                EventContent::UserInput(input),
+                Some(ChapterPick::UserInput {
+                    choice: first_choice,
+                    ..
+                }),
+                Some(ChapterPick::UserInput {
+                    choice: second_choice,
+                    ..
+                }),
+            ) => {
+                result.compared += 1;
+                if first_choice == second_choice {
+                    result.agreements += 1;
+                    continue;
+                }
+
+                let correct = |choice: &String| {
+                    input
+                        .acceptable_answers
+                        .as_ref()
+                        .map(|answers| answers.contains(choice))
+                };
+                result.disagreements.push(Disagreement {
+                    chapter_id: event.chapter_id,
+                    matchup: Matchup::Question {
+                        title: input.title.clone(),
+                    },
+                    first_choice: first_choice.clone(),
+                    second_choice: second_choice.clone(),
+                    winner: winner(correct(first_choice), correct(second_choice)),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    result
+}
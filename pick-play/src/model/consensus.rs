@@ -0,0 +1,129 @@
+use sqlx::PgPool;
+
+/// How the book as a whole picked a single game
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Consensus {
+    pub home_picks: i32,
+    pub away_picks: i32,
+    pub home_wager: i32,
+    pub away_wager: i32,
+}
+
+impl Consensus {
+    pub fn add(&mut self, choice: &str, wager: i32) {
+        match choice {
+            "home" => {
+                self.home_picks += 1;
+                self.home_wager += wager;
+            }
+            "away" => {
+                self.away_picks += 1;
+                self.away_wager += wager;
+            }
+            _ => (),
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.home_picks + self.away_picks
+    }
+
+    pub fn home_percent(&self) -> f64 {
+        percent(self.home_picks, self.total())
+    }
+
+    pub fn away_percent(&self) -> f64 {
+        percent(self.away_picks, self.total())
+    }
+
+    pub fn home_confidence(&self) -> Option<f64> {
+        (self.home_picks > 0).then(|| self.home_wager as f64 / self.home_picks as f64)
+    }
+
+    pub fn away_confidence(&self) -> Option<f64> {
+        (self.away_picks > 0).then(|| self.away_wager as f64 / self.away_picks as f64)
+    }
+
+    /// The side most of the book picked, `None` on a tie
+    pub fn majority(&self) -> Option<&'static str> {
+        match self.home_picks.cmp(&self.away_picks) {
+            std::cmp::Ordering::Greater => Some("home"),
+            std::cmp::Ordering::Less => Some("away"),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Picking the side fewer members took
+    pub fn is_contrarian(&self, choice: &str) -> bool {
+        self.majority().is_some_and(|majority| majority != choice)
+    }
+}
+
+fn percent(part: i32, total: i32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64 * 100.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsensusRecord {
+    pub wins: i32,
+    pub losses: i32,
+    pub pushes: i32,
+}
+
+/// Record of the majority pick on every graded game in the book's closed
+/// chapters. Games the book split evenly on are left out.
+pub async fn season_record(book_id: i32, pool: &PgPool) -> Result<ConsensusRecord, sqlx::Error> {
+    sqlx::query_as!(
+        ConsensusRecord,
+        r#"
+        WITH spread_picks AS (
+            SELECT
+                e.id AS event_id,
+                s.idx,
+                s.spread ->> 'answer' AS answer,
+                c.choice #>> '{}' AS choice
+            FROM picks AS p
+            JOIN events AS e ON p.event_id = e.id
+            JOIN chapters AS ch ON e.chapter_id = ch.id
+            JOIN subscriptions AS sub ON sub.user_id = p.user_id AND sub.book_id = p.book_id
+            CROSS JOIN LATERAL JSONB_ARRAY_ELEMENTS(e.contents -> 'spread_group') WITH ORDINALITY AS s(spread, idx)
+            JOIN LATERAL JSONB_ARRAY_ELEMENTS(p.choice) WITH ORDINALITY AS c(choice, idx) ON c.idx = s.idx
+            WHERE p.book_id = $1
+                AND e.event_type = 'spread_group'
+                AND NOT ch.is_open
+                AND NOT (sub.role ? 'spectator')
+        ),
+        games AS (
+            SELECT
+                answer,
+                COUNT(*) FILTER (WHERE choice = 'home') AS home_picks,
+                COUNT(*) FILTER (WHERE choice = 'away') AS away_picks
+            FROM spread_picks
+            WHERE answer IN ('home', 'away', 'push')
+            GROUP BY event_id, idx, answer
+        ),
+        consensus AS (
+            SELECT
+                answer,
+                CASE
+                    WHEN home_picks > away_picks THEN 'home'
+                    WHEN away_picks > home_picks THEN 'away'
+                END AS pick
+            FROM games
+        )
+        SELECT
+            COUNT(*) FILTER (WHERE answer = pick)::INT AS "wins!",
+            COUNT(*) FILTER (WHERE answer <> 'push' AND answer <> pick)::INT AS "losses!",
+            COUNT(*) FILTER (WHERE answer = 'push')::INT AS "pushes!"
+        FROM consensus
+        WHERE pick IS NOT NULL
+        "#,
+        book_id
+    )
+    .fetch_one(pool)
+    .await
+}
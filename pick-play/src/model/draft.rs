@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{types::Json, PgPool};
+
+use super::event::{Event, EventContent, Pick, UserPick};
+
+/// The pick form as the user last left it, saved whether or not it is
+/// complete. Submitting picks throws the draft away.
+#[derive(Debug, Clone)]
+pub struct PickDraft {
+    pub contents: Draft,
+    pub saved_at: String,
+}
+
+/// Drafted picks keyed by event id. Anything left blank on the form is `None`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Draft {
+    pub picks: HashMap<i32, DraftPick>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum DraftPick {
+    SpreadGroup {
+        choice: Vec<Option<String>>,
+        wager: Vec<Option<i32>>,
+    },
+    UserInput {
+        choice: Option<String>,
+    },
+}
+
+pub async fn save_draft(
+    chapter_id: i32,
+    user_id: i32,
+    entry_id: Option<i32>,
+    contents: &Draft,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO pick_drafts (chapter_id, user_id, entry_id, contents)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (chapter_id, user_id, entry_id)
+        DO UPDATE SET
+            contents = EXCLUDED.contents,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        chapter_id,
+        user_id,
+        entry_id,
+        Json(contents) as _
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn get_draft(
+    chapter_id: i32,
+    user_id: i32,
+    entry_id: Option<i32>,
+    pool: &PgPool,
+) -> Result<Option<PickDraft>, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        SELECT
+            contents,
+            TO_CHAR(updated_at, 'YYYY-MM-DD HH24:MI:SS') AS "saved_at!"
+        FROM pick_drafts
+        WHERE chapter_id = $1 AND user_id = $2 AND entry_id IS NOT DISTINCT FROM $3
+        "#,
+        chapter_id,
+        user_id,
+        entry_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|row| {
+        row.map(|row| PickDraft {
+            // Drafts saved before they were typed are dropped rather than
+            // failing the page
+            contents: serde_json::from_value(row.contents).unwrap_or_default(),
+            saved_at: row.saved_at,
+        })
+    })
+}
+
+/// Whether the entry has a submitted pick for every event in the chapter
+pub async fn has_full_picks(
+    chapter_id: i32,
+    user_id: i32,
+    entry_id: Option<i32>,
+    pool: &PgPool,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT NOT EXISTS (
+            SELECT 1
+            FROM events AS e
+            LEFT JOIN picks AS p ON e.id = p.event_id
+                AND p.user_id = $2
+                AND p.entry_id IS NOT DISTINCT FROM $3
+            WHERE e.chapter_id = $1 AND p.id IS NULL
+        ) AS "complete!"
+        "#,
+        chapter_id,
+        user_id,
+        entry_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Fills the pick form from a draft instead of the submitted picks. Drafts
+/// come straight from the form, so anything left blank is kept blank.
+/// Fills the pick form from a draft instead of the submitted picks. Anything
+/// left blank on the form is kept blank.
+pub fn apply_draft(user_picks: Vec<UserPick>, user_id: i32, draft: &Draft) -> Vec<UserPick> {
+    user_picks
+        .into_iter()
+        .map(|(event, pick)| {
+            let (choice, wager) = match (&event.contents.0, draft.picks.get(&event.id)) {
+                (EventContent::SpreadGroup(_), Some(DraftPick::SpreadGroup { choice, wager })) => (
+                    choice
+                        .iter()
+                        .map(|choice| Value::from(choice.clone()))
+                        .collect(),
+                    wager.iter().map(|wager| Value::from(*wager)).collect(),
+                ),
+                (EventContent::UserInput(_), Some(DraftPick::UserInput { choice })) => {
+                    (Value::from(choice.clone()), Value::from(1))
+                }
+                _ => return (event, pick),
+            };
+
+            let pick = Pick {
+                // Drafted picks haven't been stored yet
+                id: pick.as_ref().map_or(0, |pick| pick.id),
+                book_id: event.book_id,
+                chapter_id: event.chapter_id,
+                event_id: event.id,
+                user_id,
+                wager,
+                choice,
+                points: None,
+            };
+            (event, Some(pick))
+        })
+        .collect()
+}
+
+/// Keeps the drafted picks that fit the chapter's events. Unknown events are
+/// dropped, spread picks are cut to the event's spreads, and anything that
+/// isn't a valid choice or wager is left blank.
+pub fn fit_draft(events: &[Event], picks: impl IntoIterator<Item = (i32, DraftPick)>) -> Draft {
+    let picks = picks
+        .into_iter()
+        .filter_map(|(event_id, pick)| {
+            let event = events.iter().find(|event| event.id == event_id)?;
+            let pick = match (&event.contents.0, pick) {
+                (EventContent::SpreadGroup(spreads), DraftPick::SpreadGroup { choice, wager }) => {
+                    let max_wager = spreads.len() as i32;
+                    DraftPick::SpreadGroup {
+                        choice: (0..spreads.len())
+                            .map(|index| {
+                                choice
+                                    .get(index)
+                                    .cloned()
+                                    .flatten()
+                                    .filter(|choice| matches!(choice.as_str(), "home" | "away"))
+                            })
+                            .collect(),
+                        wager: (0..spreads.len())
+                            .map(|index| {
+                                wager
+                                    .get(index)
+                                    .copied()
+                                    .flatten()
+                                    .filter(|wager| (1..=max_wager).contains(wager))
+                            })
+                            .collect(),
+                    }
+                }
+                (EventContent::UserInput(_), pick @ DraftPick::UserInput { .. }) => pick,
+                _ => return None,
+            };
+            Some((event_id, pick))
+        })
+        .collect();
+
+    Draft { picks }
+}
@@ -0,0 +1,145 @@
+use sqlx::PgPool;
+
+/// An extra entry a member plays in the book alongside their own, with its
+/// own picks and leaderboard row. Picks without an entry are the member's own.
+#[derive(Debug, Clone)]
+pub struct BookEntry {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OwnedEntries {
+    /// Managed members the user makes picks for
+    pub managed: Vec<(i32, String)>,
+    /// The user's extra entries
+    pub entries: Vec<BookEntry>,
+    /// Whether the book lets the user open another entry
+    pub can_add: bool,
+}
+
+/// How many entries each member may play in the book, counting their own
+pub async fn max_entries(book_id: i32, pool: &PgPool) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar!("SELECT max_entries FROM books WHERE id = $1", book_id)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn set_max_entries(
+    book_id: i32,
+    max_entries: i32,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE books SET max_entries = $2 WHERE id = $1",
+        book_id,
+        max_entries
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+/// Extra entries `owner_id` has opened in the book
+pub async fn book_entries(
+    owner_id: i32,
+    book_id: i32,
+    executor: impl sqlx::PgExecutor<'_>,
+) -> Result<Vec<BookEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        BookEntry,
+        r#"
+        SELECT id, name
+        FROM book_entries
+        WHERE user_id = $1 AND book_id = $2
+        ORDER BY name
+        "#,
+        owner_id,
+        book_id
+    )
+    .fetch_all(executor)
+    .await
+}
+
+/// The entry, if `owner_id` opened it in the book
+pub async fn get_entry(
+    entry_id: i32,
+    owner_id: i32,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Option<BookEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        BookEntry,
+        r#"
+        SELECT id, name
+        FROM book_entries
+        WHERE id = $1 AND user_id = $2 AND book_id = $3
+        "#,
+        entry_id,
+        owner_id,
+        book_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn owned_entries(
+    owner_id: i32,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<OwnedEntries, sqlx::Error> {
+    let managed = super::managed::owned_users(owner_id, book_id, pool).await?;
+    let entries = book_entries(owner_id, book_id, pool).await?;
+    let max_entries = max_entries(book_id, pool).await?;
+
+    Ok(OwnedEntries {
+        managed,
+        can_add: entries.len() as i32 + 1 < max_entries,
+        entries,
+    })
+}
+
+/// Opens another entry for `owner_id` in the book. Returns `None` when the
+/// owner already has an entry with that name or the book doesn't allow
+/// another one.
+pub async fn create_entry(
+    owner_id: i32,
+    name: &str,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Option<i32>, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    // Locking the book makes concurrent requests wait for this count
+    let max_entries = sqlx::query_scalar!(
+        "SELECT max_entries FROM books WHERE id = $1 FOR UPDATE",
+        book_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    let count = book_entries(owner_id, book_id, &mut *transaction)
+        .await?
+        .len() as i32;
+    if count + 1 >= max_entries {
+        return Ok(None);
+    }
+
+    let entry_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO book_entries (book_id, user_id, name)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (book_id, user_id, name) DO NOTHING
+        RETURNING id
+        "#,
+        book_id,
+        owner_id,
+        name
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(entry_id)
+}
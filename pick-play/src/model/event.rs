@@ -46,7 +46,10 @@ pub struct Pick {
 
 pub type UserPick = (Event, Option<Pick>);
 
-pub async fn get_events(chapter_id: i32, pool: &PgPool) -> Result<Vec<Event>, sqlx::Error> {
+pub async fn get_events(
+    chapter_id: i32,
+    executor: impl sqlx::PgExecutor<'_>,
+) -> Result<Vec<Event>, sqlx::Error> {
     sqlx::query_as!(
         Event,
         r#"	SELECT  id,
@@ -61,12 +64,45 @@ pub async fn get_events(chapter_id: i32, pool: &PgPool) -> Result<Vec<Event>, sq
 		"#,
         chapter_id
     )
+    .fetch_all(executor)
+    .await
+}
+
+/// Events from every closed, visible chapter of a book, in chapter order.
+/// `chapter_ids` limits them to the chapters a guest was invited to.
+pub async fn get_closed_book_events(
+    book_id: i32,
+    chapter_ids: Option<&[i32]>,
+    pool: &PgPool,
+) -> Result<Vec<Event>, sqlx::Error> {
+    sqlx::query_as!(
+        Event,
+        r#"	SELECT  e.id,
+                    e.book_id,
+                    e.chapter_id,
+                    e.is_open,
+                    e.contents AS "contents: Json<EventContent>",
+                    e.event_type AS "event_type: EventType"
+			FROM events AS e
+            JOIN chapters AS c ON e.chapter_id = c.id
+			WHERE e.book_id = $1
+                AND NOT c.is_open
+                AND c.is_visible
+                AND ($2::INT[] IS NULL OR c.id = ANY($2))
+            ORDER BY c.position, e.event_type, e.id
+		"#,
+        book_id,
+        chapter_ids
+    )
     .fetch_all(pool)
     .await
 }
 
+/// Every event in the chapter with the user's pick on it, for their own
+/// entry or `entry_id`
 pub async fn get_picks(
     user_id: i32,
+    entry_id: Option<i32>,
     chapter_id: i32,
     pool: &PgPool,
 ) -> Result<Vec<UserPick>, sqlx::Error> {
@@ -86,13 +122,14 @@ pub async fn get_picks(
             LEFT JOIN (
                 SELECT *
                 FROM picks
-                WHERE user_id = $1 AND chapter_id = $2
+                WHERE user_id = $1 AND chapter_id = $2 AND entry_id IS NOT DISTINCT FROM $3
             ) AS p ON e.id = p.event_id
             WHERE e.chapter_id = $2
             ORDER BY event_type
         "#,
         user_id,
-        chapter_id
+        chapter_id,
+        entry_id
     )
     .fetch_all(pool)
     .await
@@ -142,6 +179,8 @@ pub struct ChapterPicksQuery {
 pub struct ChapterPickHash {
     pub event_id: i32,
     pub user_id: i32,
+    /// `None` for the member's own entry
+    pub entry_id: Option<i32>,
 }
 
 #[derive(serde::Deserialize)]
@@ -168,6 +207,7 @@ pub async fn get_chapter_picks(
             SELECT
                 EVENTS.ID AS "event_id",
                 PICKS.USER_ID,
+                PICKS.ENTRY_ID,
                 JSONB_BUILD_OBJECT(
                     'type',
                     EVENTS.EVENT_TYPE,
@@ -195,3 +235,52 @@ pub async fn get_chapter_picks(
         .map(|record| (record.key, record.contents))
         .collect())
 }
+
+/// Like [`get_chapter_picks`] but across the same chapters as
+/// [`get_closed_book_events`], for only the given users' own entries
+pub async fn get_closed_book_picks(
+    book_id: i32,
+    chapter_ids: Option<&[i32]>,
+    user_ids: &[i32],
+    pool: &PgPool,
+) -> Result<HashMap<ChapterPickHash, ChapterPick>, sqlx::Error> {
+    let records = sqlx::query_as::<_, ChapterPicksQuery>(
+        r#"
+            SELECT
+                EVENTS.ID AS "event_id",
+                PICKS.USER_ID,
+                PICKS.ENTRY_ID,
+                JSONB_BUILD_OBJECT(
+                    'type',
+                    EVENTS.EVENT_TYPE,
+                    'choice',
+                    PICKS.CHOICE,
+                    'wager',
+                    PICKS.WAGER,
+                    'points',
+                    PICKS.POINTS
+                ) AS contents
+            FROM
+                EVENTS
+                JOIN PICKS ON EVENTS.ID = PICKS.EVENT_ID
+                JOIN CHAPTERS ON EVENTS.CHAPTER_ID = CHAPTERS.ID
+            WHERE
+                EVENTS.BOOK_ID = $1
+                AND NOT CHAPTERS.IS_OPEN
+                AND CHAPTERS.IS_VISIBLE
+                AND ($3::INT[] IS NULL OR CHAPTERS.ID = ANY($3))
+                AND PICKS.USER_ID = ANY($2)
+                AND PICKS.ENTRY_ID IS NULL
+        "#,
+    )
+    .bind(book_id)
+    .bind(user_ids)
+    .bind(chapter_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| (record.key, record.contents))
+        .collect())
+}
@@ -0,0 +1,123 @@
+use sqlx::PgPool;
+
+/// Lets one member keep submitting picks after the chapter locks
+#[derive(Debug, Clone)]
+pub struct DeadlineExtension {
+    pub user_id: i32,
+    pub username: String,
+    pub expires_at: String,
+    pub granted_by: String,
+    pub is_active: bool,
+}
+
+pub async fn chapter_extensions(
+    chapter_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<DeadlineExtension>, sqlx::Error> {
+    sqlx::query_as!(
+        DeadlineExtension,
+        r#"
+        SELECT
+            x.user_id,
+            u.username,
+            TO_CHAR(x.expires_at, 'YYYY-MM-DD HH24:MI') AS "expires_at!",
+            g.username AS granted_by,
+            (x.expires_at > CURRENT_TIMESTAMP) AS "is_active!"
+        FROM deadline_extensions AS x
+        JOIN users AS u ON x.user_id = u.id
+        JOIN users AS g ON x.granted_by = g.id
+        WHERE x.chapter_id = $1
+        ORDER BY x.expires_at DESC
+        "#,
+        chapter_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Extends `user_id`'s deadline to `hours` from now, replacing any earlier
+/// extension
+pub async fn grant_extension(
+    chapter_id: i32,
+    user_id: i32,
+    hours: i32,
+    granted_by: i32,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO deadline_extensions (chapter_id, user_id, expires_at, granted_by)
+        VALUES ($1, $2, CURRENT_TIMESTAMP + MAKE_INTERVAL(hours => $3), $4)
+        ON CONFLICT (chapter_id, user_id)
+        DO UPDATE SET
+            expires_at = EXCLUDED.expires_at,
+            granted_by = EXCLUDED.granted_by,
+            created_at = CURRENT_TIMESTAMP
+        "#,
+        chapter_id,
+        user_id,
+        hours,
+        granted_by
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn revoke_extension(
+    chapter_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM deadline_extensions WHERE chapter_id = $1 AND user_id = $2",
+        chapter_id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn has_active_extension(
+    chapter_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM deadline_extensions
+            WHERE chapter_id = $1 AND user_id = $2 AND expires_at > CURRENT_TIMESTAMP
+        ) AS "active!"
+        "#,
+        chapter_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// A chapter of the book where the user is still submitting under an
+/// extension, while other members' picks must stay hidden from them
+pub async fn active_extension_chapter(
+    book_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<Option<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT x.chapter_id
+        FROM deadline_extensions AS x
+        JOIN chapters AS c ON x.chapter_id = c.id
+        WHERE c.book_id = $1 AND x.user_id = $2 AND x.expires_at > CURRENT_TIMESTAMP
+        ORDER BY c.position
+        LIMIT 1
+        "#,
+        book_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
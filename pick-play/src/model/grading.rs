@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, PgPool, Postgres, Transaction};
+
+use super::{chapter::Chapter, event::EventContent};
+
+/// One event's answers before and after a grading pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventChange {
+    pub event_id: i32,
+    pub before: EventContent,
+    pub after: EventContent,
+}
+
+/// A member's chapter points before and after a grading pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointDelta {
+    pub user_id: i32,
+    /// Set when the points belong to one of the member's extra entries
+    #[serde(default)]
+    pub entry_id: Option<i32>,
+    pub username: String,
+    pub before: i32,
+    pub after: i32,
+}
+
+impl PointDelta {
+    pub fn change(&self) -> i32 {
+        self.after - self.before
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GradingChange {
+    pub id: i32,
+    pub username: String,
+    pub created_at: String,
+    pub events: Json<Vec<EventChange>>,
+    pub point_deltas: Json<Vec<PointDelta>>,
+    /// Set when this change undid everything from the given change onwards
+    pub reverted_change_id: Option<i32>,
+}
+
+/// Chapter points for each entry, keyed by user and entry
+async fn chapter_points(
+    chapter_id: i32,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<Vec<((i32, Option<i32>), String, i32)>, sqlx::Error> {
+    Ok(sqlx::query!(
+        r#"
+        SELECT
+            p.user_id,
+            p.entry_id,
+            COALESCE(u.username || ' (' || e.name || ')', u.username) AS "username!",
+            COALESCE(SUM(p.points), 0)::INT AS "points!"
+        FROM picks AS p
+        JOIN users AS u ON p.user_id = u.id
+        LEFT JOIN book_entries AS e ON p.entry_id = e.id
+        WHERE p.chapter_id = $1
+        GROUP BY p.user_id, p.entry_id, u.username, e.name
+        ORDER BY u.username, e.name NULLS FIRST
+        "#,
+        chapter_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| ((row.user_id, row.entry_id), row.username, row.points))
+    .collect())
+}
+
+/// Scores every pick on the given events against their current answers
+pub async fn grade_picks(
+    chapter_id: i32,
+    event_ids: &[i32],
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "
+        UPDATE PICKS
+        SET
+            POINTS = CALCULATIONS.POINTS_AWARDED
+        FROM
+            (
+                SELECT
+                    PICK_ID,
+                    SUM(POINTS_AWARDED) AS POINTS_AWARDED
+                FROM
+                    (
+                        SELECT
+                            PICK_ID,
+                            CASE
+                                WHEN CORRECT THEN WAGER
+                                ELSE 0
+                            END AS POINTS_AWARDED
+                        FROM
+                            (
+                                SELECT
+                                    P.ID AS PICK_ID,
+                                    JSONB_ARRAY_ELEMENTS(P.WAGER)::INT AS WAGER,
+                                    JSONB_ARRAY_ELEMENTS(E.CONTENTS -> 'spread_group') ->> 'answer' = JSONB_ARRAY_ELEMENTS(P.CHOICE) #>> '{}' AS CORRECT
+                                FROM
+                                    EVENTS AS E
+                                    JOIN PICKS AS P ON E.ID = P.EVENT_ID
+                                WHERE
+                                    E.EVENT_TYPE = 'spread_group'
+                                    AND E.CHAPTER_ID = $1
+                                    AND E.ID = ANY($2)
+                            ) as t2
+                    ) as t1
+                GROUP BY
+                    PICK_ID
+                UNION
+                SELECT
+                    P.ID AS PICK_ID,
+                    CASE
+                        WHEN E.CONTENTS -> 'user_input' -> 'acceptable_answers' @> P.CHOICE THEN P.WAGER::INTEGER
+                        ELSE 0
+                    END AS POINTS_AWARDED
+                FROM
+                    EVENTS AS E
+                    JOIN PICKS AS P ON E.ID = P.EVENT_ID
+                WHERE
+                    E.EVENT_TYPE = 'user_input'
+                    AND E.CHAPTER_ID = $1
+                    AND E.ID = ANY($2)
+            ) AS CALCULATIONS
+        WHERE
+            PICKS.ID = CALCULATIONS.PICK_ID
+        ",
+        chapter_id,
+        event_ids
+    ).execute(&mut **transaction)
+    .await
+    ?;
+
+    // Picks on events with nothing graded yet have no points rather than zero
+    sqlx::query!(
+        "
+        UPDATE PICKS
+        SET
+            POINTS = NULL
+        FROM
+            EVENTS AS E
+        WHERE
+            PICKS.EVENT_ID = E.ID
+            AND E.ID = ANY($1)
+            AND CASE
+                WHEN E.EVENT_TYPE = 'spread_group' THEN NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        JSONB_ARRAY_ELEMENTS(E.CONTENTS -> 'spread_group') AS S (SPREAD)
+                    WHERE
+                        S.SPREAD ->> 'answer' IS NOT NULL
+                )
+                ELSE JSONB_TYPEOF(E.CONTENTS -> 'user_input' -> 'acceptable_answers') IS DISTINCT FROM 'array'
+            END
+        ",
+        event_ids
+    )
+    .execute(&mut **transaction)
+    .await
+    ?;
+
+    Ok(())
+}
+
+/// Saves the new answers, regrades the picks on the changed events and logs
+/// the change along with how it moved each member's chapter points
+pub async fn apply_grades(
+    chapter: &Chapter,
+    graded_by: i32,
+    changes: Vec<EventChange>,
+    reverted_change_id: Option<i32>,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    let ids = changes
+        .iter()
+        .map(|change| change.event_id)
+        .collect::<Vec<_>>();
+    let contents = changes
+        .iter()
+        .map(|change| {
+            serde_json::to_value(&change.after).expect("Failed to serialize event contents to JSON")
+        })
+        .collect::<Vec<_>>();
+
+    let mut transaction = pool.begin().await?;
+
+    let points_before = chapter_points(chapter.chapter_id, &mut transaction).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE events AS e
+        SET contents = c.contents
+        FROM UNNEST($1::INT[], $2::JSONB[]) AS c(id, contents)
+        WHERE e.id = c.id
+        "#,
+        &ids,
+        contents.as_slice()
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    grade_picks(chapter.chapter_id, &ids, &mut transaction).await?;
+
+    let point_deltas = chapter_points(chapter.chapter_id, &mut transaction)
+        .await?
+        .into_iter()
+        .filter_map(|((user_id, entry_id), username, after)| {
+            let before = points_before
+                .iter()
+                .find(|(key, ..)| *key == (user_id, entry_id))
+                .map_or(0, |(.., points)| *points);
+            (before != after).then_some(PointDelta {
+                user_id,
+                entry_id,
+                username,
+                before,
+                after,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO grading_changes (book_id, chapter_id, user_id, events, point_deltas, reverted_change_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        chapter.book_id,
+        chapter.chapter_id,
+        graded_by,
+        Json(&changes) as _,
+        Json(&point_deltas) as _,
+        reverted_change_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await
+}
+
+/// Grading changes for a chapter, newest first
+pub async fn grading_history(
+    chapter_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<GradingChange>, sqlx::Error> {
+    sqlx::query_as!(
+        GradingChange,
+        r#"
+        SELECT
+            g.id,
+            u.username,
+            TO_CHAR(g.created_at, 'YYYY-MM-DD HH24:MI') AS "created_at!",
+            g.events AS "events: Json<Vec<EventChange>>",
+            g.point_deltas AS "point_deltas: Json<Vec<PointDelta>>",
+            g.reverted_change_id
+        FROM grading_changes AS g
+        JOIN users AS u ON g.user_id = u.id
+        WHERE g.chapter_id = $1
+        ORDER BY g.id DESC
+        "#,
+        chapter_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Answers that put the chapter back the way it was before `change_id`. For
+/// each event, that is its state before the first change from then on that
+/// touched it. `history` must be newest first.
+pub fn revert_to_before(
+    change_id: i32,
+    history: &[GradingChange],
+    current: &[super::event::Event],
+) -> Vec<EventChange> {
+    current
+        .iter()
+        .filter_map(|event| {
+            let before = history
+                .iter()
+                .filter(|change| change.id >= change_id)
+                .flat_map(|change| change.events.0.iter())
+                .rfind(|change| change.event_id == event.id)?;
+            Some(EventChange {
+                event_id: event.id,
+                before: event.contents.0.clone(),
+                after: before.before.clone(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+use itertools::Itertools;
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct BookGroup {
+    pub name: String,
+    pub members: Vec<(i32, String)>,
+}
+
+pub async fn get_book_groups(book_id: i32, pool: &PgPool) -> Result<Vec<BookGroup>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT sg.name, u.id AS user_id, u.username
+        FROM subscription_groups AS sg
+        JOIN users AS u ON sg.user_id = u.id
+        WHERE sg.book_id = $1
+        ORDER BY sg.name, u.username
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .chunk_by(|row| row.name.clone())
+        .into_iter()
+        .map(|(name, rows)| BookGroup {
+            name,
+            members: rows.map(|row| (row.user_id, row.username)).collect(),
+        })
+        .collect())
+}
+
+pub async fn add_group_member(
+    book_id: i32,
+    name: &str,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO subscription_groups (name, user_id, book_id)
+        SELECT $1, s.user_id, s.book_id
+        FROM subscriptions AS s
+        WHERE s.user_id = $2 AND s.book_id = $3
+        ON CONFLICT DO NOTHING
+        "#,
+        name,
+        user_id,
+        book_id
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn remove_group_member(
+    book_id: i32,
+    name: &str,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM subscription_groups
+        WHERE name = $1 AND user_id = $2 AND book_id = $3
+        "#,
+        name,
+        user_id,
+        book_id
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct GroupStanding {
+    pub name: String,
+    pub num_members: i32,
+    pub total_points: i32,
+    pub average_points: f64,
+    pub rank: i32,
+}
+
+/// Groups are ranked on their members' average so that bigger groups don't win by default
+pub async fn group_leaderboard(
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<GroupStanding>, sqlx::Error> {
+    sqlx::query_as!(
+        GroupStanding,
+        r#"
+        WITH member_points AS (
+            SELECT
+                s.user_id,
+                COALESCE((
+                    SELECT SUM(p.points)
+                    FROM picks AS p
+                    WHERE p.book_id = s.book_id AND p.user_id = s.user_id AND p.entry_id IS NULL
+                ), 0) +
+                COALESCE((
+                    SELECT SUM(ap.points)
+                    FROM added_points AS ap
+                    WHERE ap.book_id = s.book_id AND ap.user_id = s.user_id
+                ), 0) AS points
+            FROM subscriptions AS s
+            WHERE s.book_id = $1 AND NOT (s.role ? 'spectator')
+        ),
+        group_points AS (
+            SELECT
+                sg.name,
+                COUNT(*) AS num_members,
+                SUM(mp.points) AS total_points,
+                AVG(mp.points) AS average_points
+            FROM subscription_groups AS sg
+            JOIN member_points AS mp ON sg.user_id = mp.user_id
+            WHERE sg.book_id = $1
+            GROUP BY sg.name
+        )
+        SELECT
+            name AS "name!",
+            num_members::INT AS "num_members!",
+            total_points::INT AS "total_points!",
+            average_points::FLOAT8 AS "average_points!",
+            RANK() OVER (ORDER BY average_points DESC)::INT AS "rank!"
+        FROM group_points
+        ORDER BY average_points DESC, name
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,145 @@
+use sqlx::{Connection, PgConnection, PgPool};
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct BookInvite {
+    pub code: String,
+    pub book_id: i32,
+    pub expires_at: Option<String>,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub is_active: bool,
+}
+
+pub async fn create_invite(
+    book_id: i32,
+    created_by: i32,
+    expires_in_days: Option<i32>,
+    max_uses: Option<i32>,
+    pool: &PgPool,
+) -> Result<BookInvite, sqlx::Error> {
+    sqlx::query_as!(
+        BookInvite,
+        r#"
+        INSERT INTO book_invites (book_id, created_by, expires_at, max_uses)
+        VALUES ($1, $2, CURRENT_TIMESTAMP + make_interval(days => $3), $4)
+        RETURNING
+            code,
+            book_id,
+            to_char(expires_at, 'YYYY-MM-DD HH24:MI') AS expires_at,
+            max_uses,
+            uses,
+            TRUE AS "is_active!"
+        "#,
+        book_id,
+        created_by,
+        expires_in_days,
+        max_uses
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_book_invites(book_id: i32, pool: &PgPool) -> Result<Vec<BookInvite>, sqlx::Error> {
+    sqlx::query_as!(
+        BookInvite,
+        r#"
+        SELECT
+            code,
+            book_id,
+            to_char(expires_at, 'YYYY-MM-DD HH24:MI') AS expires_at,
+            max_uses,
+            uses,
+            (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+                AND (max_uses IS NULL OR uses < max_uses) AS "is_active!"
+        FROM book_invites
+        WHERE book_id = $1
+        ORDER BY created_at DESC
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete_invite(code: &str, book_id: i32, pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM book_invites
+        WHERE code = $1 AND book_id = $2
+        "#,
+        code,
+        book_id
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+/// Subscribes the user to the invite's book, returning the book id.
+/// Returns `None` if the code is unknown, expired, or out of uses.
+/// Members who are already subscribed don't use up the invite.
+pub async fn redeem_invite(
+    code: &str,
+    user_id: i32,
+    conn: &mut PgConnection,
+) -> Result<Option<i32>, sqlx::Error> {
+    let code = code.trim().to_uppercase();
+    let mut transaction = conn.begin().await?;
+
+    // Claiming a use in the same statement that checks the limit keeps
+    // concurrent redemptions from going over it
+    let claimed = sqlx::query_scalar!(
+        r#"
+        UPDATE book_invites
+        SET uses = uses + 1
+        WHERE code = $1
+            AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+            AND (max_uses IS NULL OR uses < max_uses)
+            AND NOT EXISTS (
+                SELECT 1 FROM subscriptions
+                WHERE user_id = $2 AND book_id = book_invites.book_id
+            )
+        RETURNING book_id
+        "#,
+        code,
+        user_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let Some(book_id) = claimed else {
+        let subscribed = sqlx::query_scalar!(
+            r#"
+            SELECT i.book_id
+            FROM book_invites AS i
+            JOIN subscriptions AS s ON s.book_id = i.book_id AND s.user_id = $2
+            WHERE i.code = $1
+                AND (i.expires_at IS NULL OR i.expires_at > CURRENT_TIMESTAMP)
+            "#,
+            code,
+            user_id
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+        return Ok(subscribed);
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO subscriptions (user_id, book_id, role)
+        VALUES ($1, $2, to_jsonb('participant'::TEXT))
+        ON CONFLICT (user_id, book_id)
+        DO NOTHING
+        "#,
+        user_id,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(Some(book_id))
+}
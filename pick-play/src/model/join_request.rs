@@ -0,0 +1,156 @@
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct JoinRequest {
+    pub id: i32,
+    pub book_id: i32,
+    pub book_name: String,
+    pub user_id: i32,
+    pub username: String,
+    /// One of `pending`, `approved` or `rejected`
+    pub status: String,
+}
+
+pub async fn get_requestable_book(
+    book_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<Option<(String, bool)>, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        SELECT
+            b.name,
+            EXISTS (
+                SELECT 1
+                FROM subscriptions AS s
+                WHERE s.book_id = b.id AND s.user_id = $2
+            ) AS "is_member!"
+        FROM books AS b
+        WHERE b.id = $1
+        "#,
+        book_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|row| row.map(|r| (r.name, r.is_member)))
+}
+
+/// Creates a pending request, or re-opens one that was previously rejected
+pub async fn request_to_join(book_id: i32, user_id: i32, pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO join_requests (book_id, user_id)
+        VALUES ($1, $2)
+        ON CONFLICT (book_id, user_id)
+        DO UPDATE SET
+            status = 'pending',
+            resolved_by = NULL,
+            resolved_at = NULL,
+            created_at = CURRENT_TIMESTAMP
+        "#,
+        book_id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn pending_book_requests(
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<JoinRequest>, sqlx::Error> {
+    sqlx::query_as!(
+        JoinRequest,
+        r#"
+        SELECT
+            jr.id,
+            jr.book_id,
+            b.name AS book_name,
+            jr.user_id,
+            u.username,
+            jr.status
+        FROM join_requests AS jr
+        JOIN books AS b ON jr.book_id = b.id
+        JOIN users AS u ON jr.user_id = u.id
+        WHERE jr.book_id = $1 AND jr.status = 'pending'
+        ORDER BY jr.created_at
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn user_join_requests(
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<JoinRequest>, sqlx::Error> {
+    sqlx::query_as!(
+        JoinRequest,
+        r#"
+        SELECT
+            jr.id,
+            jr.book_id,
+            b.name AS book_name,
+            jr.user_id,
+            u.username,
+            jr.status
+        FROM join_requests AS jr
+        JOIN books AS b ON jr.book_id = b.id
+        JOIN users AS u ON jr.user_id = u.id
+        WHERE jr.user_id = $1
+        ORDER BY jr.created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Marks a pending request as resolved, returning the requesting user's id
+pub async fn resolve_join_request(
+    request_id: i32,
+    book_id: i32,
+    resolved_by: i32,
+    approved: bool,
+    executor: impl sqlx::PgExecutor<'_>,
+) -> Result<Option<i32>, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE join_requests
+        SET
+            status = CASE WHEN $4 THEN 'approved' ELSE 'rejected' END,
+            resolved_by = $3,
+            resolved_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND book_id = $2 AND status = 'pending'
+        RETURNING user_id
+        "#,
+        request_id,
+        book_id,
+        resolved_by,
+        approved
+    )
+    .fetch_optional(executor)
+    .await
+    .map(|row| row.map(|r| r.user_id))
+}
+
+pub async fn dismiss_join_request(
+    request_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM join_requests
+        WHERE id = $1 AND user_id = $2 AND status != 'pending'
+        "#,
+        request_id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
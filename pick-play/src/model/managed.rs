@@ -0,0 +1,110 @@
+use sqlx::PgPool;
+
+/// A member without login credentials whose picks are made by `owner_id`
+#[derive(Debug, Clone)]
+pub struct ManagedUser {
+    pub id: i32,
+    pub username: String,
+    pub owner_id: i32,
+    pub owner_username: String,
+}
+
+/// Creates a managed user and adds them to the book. Returns `None` when the
+/// username is already taken.
+pub async fn create_managed_user(
+    username: &str,
+    owner_id: i32,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Option<ManagedUser>, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    let Some(user) = sqlx::query!(
+        r#"
+        INSERT INTO users (username, managed_by)
+        VALUES ($1, $2)
+        ON CONFLICT (username) DO NOTHING
+        RETURNING id
+        "#,
+        username,
+        owner_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO subscriptions (user_id, book_id, role)
+        VALUES ($1, $2, to_jsonb('participant'::TEXT))
+        "#,
+        user.id,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let owner_username = sqlx::query_scalar!("SELECT username FROM users WHERE id = $1", owner_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(Some(ManagedUser {
+        id: user.id,
+        username: username.to_string(),
+        owner_id,
+        owner_username,
+    }))
+}
+
+/// Managed users in the book, with who they belong to
+pub async fn book_managed_users(
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<ManagedUser>, sqlx::Error> {
+    sqlx::query_as!(
+        ManagedUser,
+        r#"
+        SELECT
+            u.id,
+            u.username,
+            o.id AS owner_id,
+            o.username AS owner_username
+        FROM users AS u
+        JOIN users AS o ON u.managed_by = o.id
+        JOIN subscriptions AS s ON u.id = s.user_id
+        WHERE s.book_id = $1
+        ORDER BY u.username
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Managed users in the book that `owner_id` makes picks for
+pub async fn owned_users(
+    owner_id: i32,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<(i32, String)>, sqlx::Error> {
+    Ok(sqlx::query!(
+        r#"
+        SELECT u.id, u.username
+        FROM users AS u
+        JOIN subscriptions AS s ON u.id = s.user_id
+        WHERE u.managed_by = $1 AND s.book_id = $2
+        ORDER BY u.username
+        "#,
+        owner_id,
+        book_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, row.username))
+    .collect())
+}
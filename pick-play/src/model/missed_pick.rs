@@ -0,0 +1,517 @@
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use sqlx::{types::Json, PgPool, Postgres, Transaction};
+
+use super::{
+    event::{get_events, EventContent},
+    grading::grade_picks,
+    spread::Spread,
+};
+
+/// What happens to members that haven't submitted picks when a chapter locks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, sqlx::Type)]
+#[sqlx(type_name = "missed_pick_policies", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MissedPickPolicy {
+    None,
+    Random,
+    Favorites,
+    CarryForward,
+    MinimumScore,
+    AverageScore,
+}
+
+impl MissedPickPolicy {
+    pub const ALL: [MissedPickPolicy; 6] = [
+        MissedPickPolicy::None,
+        MissedPickPolicy::Random,
+        MissedPickPolicy::Favorites,
+        MissedPickPolicy::CarryForward,
+        MissedPickPolicy::MinimumScore,
+        MissedPickPolicy::AverageScore,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            MissedPickPolicy::None => "none",
+            MissedPickPolicy::Random => "random",
+            MissedPickPolicy::Favorites => "favorites",
+            MissedPickPolicy::CarryForward => "carry_forward",
+            MissedPickPolicy::MinimumScore => "minimum_score",
+            MissedPickPolicy::AverageScore => "average_score",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MissedPickPolicy::None => "No picks",
+            MissedPickPolicy::Random => "Random picks",
+            MissedPickPolicy::Favorites => "Favorites",
+            MissedPickPolicy::CarryForward => "Last week's strategy",
+            MissedPickPolicy::MinimumScore => "Lowest score",
+            MissedPickPolicy::AverageScore => "Average score",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            MissedPickPolicy::None => "Members that miss a chapter score nothing",
+            MissedPickPolicy::Random => "Random sides and confidence points",
+            MissedPickPolicy::Favorites => {
+                "Every favorite, with the most points on the biggest spreads"
+            }
+            MissedPickPolicy::CarryForward => {
+                "The previous chapter's favorite or underdog call at each confidence level"
+            }
+            MissedPickPolicy::MinimumScore => "The lowest score of the members that picked",
+            MissedPickPolicy::AverageScore => "The average score of the members that picked",
+        }
+    }
+
+    /// Whether the policy fills in picks rather than awarding a score
+    fn makes_picks(&self) -> bool {
+        matches!(
+            self,
+            MissedPickPolicy::Random | MissedPickPolicy::Favorites | MissedPickPolicy::CarryForward
+        )
+    }
+}
+
+pub async fn book_policy(book_id: i32, pool: &PgPool) -> Result<MissedPickPolicy, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT missed_pick_policy AS "policy: MissedPickPolicy" FROM books WHERE id = $1"#,
+        book_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn set_book_policy(
+    book_id: i32,
+    policy: MissedPickPolicy,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE books SET missed_pick_policy = $2 WHERE id = $1",
+        book_id,
+        policy as _
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+fn favorite(spread: &Spread, take_favorite: bool) -> String {
+    let home_favored = spread.home_spread <= 0.;
+    if home_favored == take_favorite {
+        "home".to_string()
+    } else {
+        "away".to_string()
+    }
+}
+
+/// Game indices with the biggest spread first
+fn by_spread_size(spreads: &[Spread]) -> Vec<usize> {
+    let mut order = (0..spreads.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        spreads[*b]
+            .home_spread
+            .abs()
+            .total_cmp(&spreads[*a].home_spread.abs())
+    });
+    order
+}
+
+/// Confidence points from the biggest spread down, taking the favorite or
+/// underdog at each confidence level as `strategy` says
+fn ranked_picks(spreads: &[Spread], strategy: &[bool]) -> (Vec<String>, Vec<i32>) {
+    let mut choices = vec![String::new(); spreads.len()];
+    let mut wagers = vec![0; spreads.len()];
+    for (rank, index) in by_spread_size(spreads).into_iter().enumerate() {
+        choices[index] = favorite(&spreads[index], strategy.get(rank).copied().unwrap_or(true));
+        wagers[index] = (spreads.len() - rank) as i32;
+    }
+    (choices, wagers)
+}
+
+fn random_picks(spreads: &[Spread], rng: &mut impl Rng) -> (Vec<String>, Vec<i32>) {
+    let choices = spreads
+        .iter()
+        .map(|_| {
+            if rng.gen_bool(0.5) {
+                "home".to_string()
+            } else {
+                "away".to_string()
+            }
+        })
+        .collect();
+
+    let mut wagers = (1..=spreads.len() as i32).collect::<Vec<_>>();
+    wagers.shuffle(rng);
+    (choices, wagers)
+}
+
+/// Whether the user took the favorite at each confidence level in the
+/// previous chapter, highest confidence first
+async fn previous_strategy(
+    chapter_id: i32,
+    user_id: i32,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<Vec<bool>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT e.contents AS "contents: Json<EventContent>", p.choice, p.wager
+        FROM picks AS p
+        JOIN events AS e ON p.event_id = e.id
+        WHERE p.user_id = $2 AND p.entry_id IS NULL AND p.chapter_id = (
+            SELECT prev.id
+            FROM chapters AS prev
+            JOIN chapters AS curr ON prev.book_id = curr.book_id
+            WHERE curr.id = $1 AND prev.position < curr.position
+            ORDER BY prev.position DESC
+            LIMIT 1
+        )
+        "#,
+        chapter_id,
+        user_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(strategy(rows.into_iter().filter_map(|row| {
+        let EventContent::SpreadGroup(spreads) = row.contents.0 else {
+            return None;
+        };
+        let choices = serde_json::from_value::<Vec<String>>(row.choice).unwrap_or_default();
+        let wagers = serde_json::from_value::<Vec<i32>>(row.wager).unwrap_or_default();
+        Some((spreads, choices, wagers))
+    })))
+}
+
+/// Whether each pick took the favorite, ordered by its wager from highest
+/// to lowest
+fn strategy(picks: impl Iterator<Item = (Vec<Spread>, Vec<String>, Vec<i32>)>) -> Vec<bool> {
+    let mut calls = picks
+        .flat_map(|(spreads, choices, wagers)| {
+            spreads
+                .iter()
+                .zip(choices)
+                .zip(wagers)
+                .map(|((spread, choice), wager)| (wager, choice == favorite(spread, true)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    calls.sort_by_key(|(wager, _)| std::cmp::Reverse(*wager));
+
+    calls
+        .into_iter()
+        .map(|(_, took_favorite)| took_favorite)
+        .collect()
+}
+
+/// Applies the book's policy to members without picks of their own as the
+/// chapter locks, leaving extra entries as they are. Picks filled in by an
+/// earlier lock are kept so reopening and locking a chapter again neither
+/// rerolls them nor loses their points, unless the policy changed, the
+/// member picked in the meantime, or they can no longer play the chapter.
+pub async fn apply_missed_pick_policy(
+    chapter_id: i32,
+    book_id: i32,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    let policy = sqlx::query_scalar!(
+        r#"SELECT missed_pick_policy AS "policy: MissedPickPolicy" FROM books WHERE id = $1"#,
+        book_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM picks AS p
+        WHERE p.chapter_id = $1
+            AND p.auto_policy IS NOT NULL
+            AND (
+                p.auto_policy <> $2
+                OR EXISTS (
+                    SELECT 1 FROM picks AS own
+                    WHERE own.chapter_id = $1
+                        AND own.user_id = p.user_id
+                        AND own.entry_id IS NULL
+                        AND own.auto_policy IS NULL
+                )
+                OR NOT EXISTS (
+                    SELECT 1 FROM subscriptions AS s
+                    WHERE s.book_id = $3
+                        AND s.user_id = p.user_id
+                        AND NOT (s.role ? 'spectator')
+                        AND COALESCE(((s.role->'guest'->'chapter_ids') @> to_jsonb($1::INT)), true)
+                )
+            )
+        "#,
+        chapter_id,
+        policy as _,
+        book_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM missed_picks AS m
+        WHERE m.chapter_id = $1
+            AND (
+                m.policy <> $2
+                OR EXISTS (
+                    SELECT 1 FROM picks AS own
+                    WHERE own.chapter_id = $1
+                        AND own.user_id = m.user_id
+                        AND own.entry_id IS NULL
+                        AND own.auto_policy IS NULL
+                )
+                OR NOT EXISTS (
+                    SELECT 1 FROM subscriptions AS s
+                    WHERE s.book_id = $3
+                        AND s.user_id = m.user_id
+                        AND NOT (s.role ? 'spectator')
+                        AND COALESCE(((s.role->'guest'->'chapter_ids') @> to_jsonb($1::INT)), true)
+                )
+            )
+        "#,
+        chapter_id,
+        policy as _,
+        book_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    if policy == MissedPickPolicy::None {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO missed_picks (chapter_id, user_id, policy)
+        SELECT $1, s.user_id, $3
+        FROM subscriptions AS s
+        WHERE s.book_id = $2
+            AND NOT (s.role ? 'spectator')
+            AND COALESCE(((s.role->'guest'->'chapter_ids') @> to_jsonb($1::INT)), true)
+            AND NOT EXISTS (
+                SELECT 1 FROM picks AS p
+                WHERE p.chapter_id = $1 AND p.user_id = s.user_id AND p.entry_id IS NULL
+            )
+        ON CONFLICT (chapter_id, user_id) DO NOTHING
+        "#,
+        chapter_id,
+        book_id,
+        policy as _
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    if !policy.makes_picks() {
+        return Ok(());
+    }
+
+    let missed = sqlx::query_scalar!(
+        "SELECT user_id FROM missed_picks WHERE chapter_id = $1",
+        chapter_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let filled = sqlx::query!(
+        "SELECT user_id, event_id FROM picks WHERE chapter_id = $1 AND auto_policy IS NOT NULL",
+        chapter_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| (row.user_id, row.event_id))
+    .collect::<HashSet<_>>();
+
+    let events = get_events(chapter_id, &mut **transaction).await?;
+    // Thread-local randomness can't be held across the inserts below
+    let mut rng = StdRng::from_entropy();
+    for user_id in missed {
+        let strategy = match policy {
+            MissedPickPolicy::CarryForward => {
+                previous_strategy(chapter_id, user_id, transaction).await?
+            }
+            _ => vec![],
+        };
+
+        // Bonus questions have no sensible answer to fill in, and games
+        // added since the last lock are the only ones still missing a pick
+        for event in &events {
+            let EventContent::SpreadGroup(spreads) = &event.contents.0 else {
+                continue;
+            };
+            if filled.contains(&(user_id, event.id)) {
+                continue;
+            }
+            let (choices, wagers) = match policy {
+                MissedPickPolicy::Random => random_picks(spreads, &mut rng),
+                _ => ranked_picks(spreads, &strategy),
+            };
+
+            sqlx::query!(
+                r#"
+                INSERT INTO picks (book_id, chapter_id, user_id, event_id, choice, wager, auto_policy)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                book_id,
+                chapter_id,
+                user_id,
+                event.id,
+                Json(choices) as _,
+                Json(wagers) as _,
+                policy as _
+            )
+            .execute(&mut **transaction)
+            .await?;
+        }
+    }
+
+    // A graded chapter being locked again has to score the new picks too,
+    // since regrading only ever touches events whose answers changed
+    let event_ids = events.iter().map(|event| event.id).collect::<Vec<_>>();
+    grade_picks(chapter_id, &event_ids, transaction).await
+}
+
+/// Scores members that missed the chapter under a score policy from what
+/// the members that picked have scored so far. Rerun after every grading.
+pub async fn apply_missed_scores(
+    chapter_id: i32,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM added_points WHERE chapter_id = $1 AND auto_policy IS NOT NULL",
+        chapter_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        WITH scores AS (
+            SELECT user_id, SUM(points) AS total
+            FROM picks
+            WHERE chapter_id = $1 AND entry_id IS NULL AND auto_policy IS NULL AND points IS NOT NULL
+            GROUP BY user_id
+        )
+        INSERT INTO added_points (user_id, book_id, points, reason, chapter_id, auto_policy)
+        SELECT
+            m.user_id,
+            $2,
+            CASE m.policy
+                WHEN 'minimum_score' THEN (SELECT MIN(total) FROM scores)
+                ELSE (SELECT ROUND(AVG(total)) FROM scores)
+            END::INT,
+            CASE m.policy
+                WHEN 'minimum_score' THEN 'Missed picks: lowest score'
+                ELSE 'Missed picks: average score'
+            END,
+            $1,
+            m.policy
+        FROM missed_picks AS m
+        WHERE m.chapter_id = $1
+            AND m.policy IN ('minimum_score', 'average_score')
+            AND EXISTS (SELECT 1 FROM scores)
+        "#,
+        chapter_id,
+        book_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spread(home_spread: f64) -> Spread {
+        Spread {
+            home_id: 1,
+            away_id: 2,
+            home_spread,
+            answer: None,
+            notes: None,
+        }
+    }
+
+    fn spread_group() -> Vec<Spread> {
+        vec![spread(-3.5), spread(7.), spread(-10.), spread(1.5)]
+    }
+
+    fn sorted(mut wagers: Vec<i32>) -> Vec<i32> {
+        wagers.sort();
+        wagers
+    }
+
+    #[test]
+    fn random_picks_use_every_wager_once() {
+        let spreads = spread_group();
+        let mut rng = StdRng::seed_from_u64(49);
+        for _ in 0..20 {
+            let (choices, wagers) = random_picks(&spreads, &mut rng);
+            assert_eq!(choices.len(), spreads.len());
+            assert!(choices
+                .iter()
+                .all(|choice| choice == "home" || choice == "away"));
+            assert_eq!(sorted(wagers), vec![1, 2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn random_picks_of_no_spreads_are_empty() {
+        let (choices, wagers) = random_picks(&[], &mut StdRng::seed_from_u64(49));
+        assert!(choices.is_empty());
+        assert!(wagers.is_empty());
+    }
+
+    #[test]
+    fn ranked_picks_back_the_biggest_spread_most() {
+        let spreads = spread_group();
+        let (choices, wagers) = ranked_picks(&spreads, &[]);
+        assert_eq!(choices, vec!["home", "away", "home", "away"]);
+        assert_eq!(wagers, vec![2, 3, 4, 1]);
+    }
+
+    #[test]
+    fn ranked_picks_follow_the_strategy_by_rank() {
+        let spreads = spread_group();
+        // Underdog on the biggest spread, favorites after that, and a
+        // strategy too short for the last game falls back to the favorite
+        let (choices, wagers) = ranked_picks(&spreads, &[false, true]);
+        assert_eq!(choices, vec!["home", "away", "away", "away"]);
+        assert_eq!(choices.len(), spreads.len());
+        assert_eq!(sorted(wagers), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn strategy_orders_calls_by_wager() {
+        let picks = vec![
+            (
+                vec![spread(-3.), spread(5.)],
+                vec!["home".to_string(), "home".to_string()],
+                vec![1, 3],
+            ),
+            (vec![spread(-1.)], vec!["away".to_string()], vec![2]),
+        ];
+        assert_eq!(strategy(picks.into_iter()), vec![false, false, true]);
+    }
+
+    #[test]
+    fn strategy_skips_short_picks() {
+        let picks = vec![(spread_group(), vec!["home".to_string()], vec![4, 3])];
+        assert_eq!(strategy(picks.into_iter()), vec![true]);
+    }
+}
@@ -0,0 +1,62 @@
+use sqlx::PgPool;
+
+/// A single event's pick as it was submitted at `submitted_at`
+#[derive(Debug, Clone)]
+pub struct PickRevision {
+    pub event_id: i32,
+    pub choice: serde_json::Value,
+    pub wager: serde_json::Value,
+    pub submitted_at: String,
+    /// Admin that entered the pick for the user
+    pub entered_by: Option<String>,
+}
+
+/// Every pick a user has submitted in a chapter for their own entry, oldest
+/// first. Picks from the same submission share a timestamp.
+pub async fn pick_revisions(
+    chapter_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<PickRevision>, sqlx::Error> {
+    sqlx::query_as!(
+        PickRevision,
+        r#"
+        SELECT
+            r.event_id,
+            r.choice,
+            r.wager,
+            TO_CHAR(r.created_at, 'YYYY-MM-DD HH24:MI:SS') AS "submitted_at!",
+            u.username AS "entered_by?"
+        FROM pick_revisions AS r
+        LEFT JOIN users AS u ON r.entered_by = u.id
+        WHERE r.chapter_id = $1 AND r.user_id = $2 AND r.entry_id IS NULL
+        ORDER BY r.created_at, r.id
+        "#,
+        chapter_id,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Users that have submitted picks in the chapter
+pub async fn revision_users(
+    chapter_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<(i32, String)>, sqlx::Error> {
+    Ok(sqlx::query!(
+        r#"
+        SELECT DISTINCT u.id, u.username
+        FROM pick_revisions AS r
+        JOIN users AS u ON r.user_id = u.id
+        WHERE r.chapter_id = $1
+        ORDER BY u.username
+        "#,
+        chapter_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, row.username))
+    .collect())
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerRanking {
@@ -9,6 +10,14 @@ pub struct PlayerRanking {
     pub score: i32,
     pub correct_guesses: i32,
     pub total_guesses: i32,
+    pub spread_correct: i32,
+    pub spread_total: i32,
+    pub question_correct: i32,
+    pub question_total: i32,
+    pub avg_confidence_wins: Option<f64>,
+    pub avg_confidence_losses: Option<f64>,
+    pub chapters_played: i32,
+    pub chapter_points: i32,
 }
 
 impl PlayerRanking {
@@ -24,58 +33,128 @@ impl PlayerRanking {
         self.accuracy().round() as i32
     }
 
+    pub fn points_per_chapter(&self) -> f64 {
+        if self.chapters_played == 0 {
+            0.0
+        } else {
+            self.chapter_points as f64 / self.chapters_played as f64
+        }
+    }
+
     pub fn avatar_initials(&self) -> String {
-        self.name.chars().next().unwrap_or('?').to_string().to_uppercase()
+        self.name
+            .chars()
+            .next()
+            .unwrap_or('?')
+            .to_string()
+            .to_uppercase()
     }
 }
 
-// Mock data for testing - in a real app this would come from the database
-pub fn mock_player_rankings() -> Vec<PlayerRanking> {
-    vec![
-        PlayerRanking {
-            id: 1,
-            name: "Alex Johnson".to_string(),
-            avatar: Some("/abstract-letter-aj.png".to_string()),
-            rank: 1,
-            score: 1030,
-            correct_guesses: 5,
-            total_guesses: 6,
-        },
-        PlayerRanking {
-            id: 2,
-            name: "Taylor Smith".to_string(),
-            avatar: Some("/abstract-geometric-ts.png".to_string()),
-            rank: 2,
-            score: 850,
-            correct_guesses: 4,
-            total_guesses: 6,
-        },
-        PlayerRanking {
-            id: 3,
-            name: "Morgan Lee".to_string(),
-            avatar: Some("/machine-learning-concept.png".to_string()),
-            rank: 3,
-            score: 820,
-            correct_guesses: 4,
-            total_guesses: 6,
-        },
-        PlayerRanking {
-            id: 4,
-            name: "Jordan Rivera".to_string(),
-            avatar: Some("/stylized-jr-logo.png".to_string()),
-            rank: 4,
-            score: 550,
-            correct_guesses: 2,
-            total_guesses: 6,
-        },
-        PlayerRanking {
-            id: 5,
-            name: "Casey Kim".to_string(),
-            avatar: Some("/abstract-ck-design.png".to_string()),
-            rank: 5,
-            score: 540,
-            correct_guesses: 3,
-            total_guesses: 6,
-        },
-    ]
+/// Only graded games and questions count towards the totals, and only from
+/// each member's own entry. Confidence is the wager placed on a spread.
+pub async fn book_player_rankings(
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<PlayerRanking>, sqlx::Error> {
+    sqlx::query_as!(
+        PlayerRanking,
+        r#"
+        WITH spread_results AS (
+            SELECT
+                p.user_id,
+                (s.spread ->> 'answer') = (c.choice #>> '{}') AS correct,
+                (w.wager #>> '{}')::INT AS wager
+            FROM picks AS p
+            JOIN events AS e ON p.event_id = e.id
+            CROSS JOIN LATERAL JSONB_ARRAY_ELEMENTS(e.contents -> 'spread_group') WITH ORDINALITY AS s(spread, idx)
+            JOIN LATERAL JSONB_ARRAY_ELEMENTS(p.choice) WITH ORDINALITY AS c(choice, idx) ON c.idx = s.idx
+            JOIN LATERAL JSONB_ARRAY_ELEMENTS(p.wager) WITH ORDINALITY AS w(wager, idx) ON w.idx = s.idx
+            WHERE p.book_id = $1
+                AND p.entry_id IS NULL
+                AND e.event_type = 'spread_group'
+                AND s.spread ->> 'answer' IN ('home', 'away')
+        ),
+        spread_stats AS (
+            SELECT
+                user_id,
+                COUNT(*) FILTER (WHERE correct) AS correct,
+                COUNT(*) AS total,
+                AVG(wager) FILTER (WHERE correct) AS avg_confidence_wins,
+                AVG(wager) FILTER (WHERE NOT correct) AS avg_confidence_losses
+            FROM spread_results
+            GROUP BY user_id
+        ),
+        question_stats AS (
+            SELECT
+                p.user_id,
+                COUNT(*) FILTER (WHERE e.contents -> 'user_input' -> 'acceptable_answers' @> p.choice) AS correct,
+                COUNT(*) AS total
+            FROM picks AS p
+            JOIN events AS e ON p.event_id = e.id
+            WHERE p.book_id = $1
+                AND p.entry_id IS NULL
+                AND e.event_type = 'user_input'
+                AND JSONB_TYPEOF(e.contents -> 'user_input' -> 'acceptable_answers') = 'array'
+            GROUP BY p.user_id
+        ),
+        chapter_stats AS (
+            SELECT
+                user_id,
+                COUNT(DISTINCT chapter_id) AS chapters_played,
+                SUM(points) AS points
+            FROM picks
+            WHERE book_id = $1 AND entry_id IS NULL AND points IS NOT NULL
+            GROUP BY user_id
+        ),
+        added AS (
+            SELECT user_id, SUM(points) AS points
+            FROM added_points
+            WHERE book_id = $1
+            GROUP BY user_id
+        ),
+        members AS (
+            SELECT
+                u.id,
+                u.username,
+                COALESCE(ss.correct, 0) AS spread_correct,
+                COALESCE(ss.total, 0) AS spread_total,
+                COALESCE(qs.correct, 0) AS question_correct,
+                COALESCE(qs.total, 0) AS question_total,
+                ss.avg_confidence_wins,
+                ss.avg_confidence_losses,
+                COALESCE(cs.chapters_played, 0) AS chapters_played,
+                COALESCE(cs.points, 0) AS chapter_points,
+                COALESCE(cs.points, 0) + COALESCE(a.points, 0) AS score
+            FROM subscriptions AS s
+            JOIN users AS u ON s.user_id = u.id
+            LEFT JOIN spread_stats AS ss ON u.id = ss.user_id
+            LEFT JOIN question_stats AS qs ON u.id = qs.user_id
+            LEFT JOIN chapter_stats AS cs ON u.id = cs.user_id
+            LEFT JOIN added AS a ON u.id = a.user_id
+            WHERE s.book_id = $1 AND NOT (s.role ? 'spectator')
+        )
+        SELECT
+            id AS "id!",
+            username AS "name!",
+            NULL::TEXT AS "avatar",
+            RANK() OVER (ORDER BY score DESC)::INT AS "rank!",
+            score::INT AS "score!",
+            (spread_correct + question_correct)::INT AS "correct_guesses!",
+            (spread_total + question_total)::INT AS "total_guesses!",
+            spread_correct::INT AS "spread_correct!",
+            spread_total::INT AS "spread_total!",
+            question_correct::INT AS "question_correct!",
+            question_total::INT AS "question_total!",
+            avg_confidence_wins::FLOAT8 AS "avg_confidence_wins",
+            avg_confidence_losses::FLOAT8 AS "avg_confidence_losses",
+            chapters_played::INT AS "chapters_played!",
+            chapter_points::INT AS "chapter_points!"
+        FROM members
+        ORDER BY score DESC, username
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
 }
@@ -0,0 +1,166 @@
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct SharedBook {
+    pub id: i32,
+    pub name: String,
+}
+
+/// Books that both users belong to. Only these books may be used for a profile.
+pub async fn shared_books(
+    viewer_id: i32,
+    user_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<SharedBook>, sqlx::Error> {
+    sqlx::query_as!(
+        SharedBook,
+        r#"
+        SELECT b.id, b.name
+        FROM books AS b
+        JOIN subscriptions AS viewer ON viewer.book_id = b.id AND viewer.user_id = $1
+        JOIN subscriptions AS target ON target.book_id = b.id AND target.user_id = $2
+        WHERE NOT (target.role ? 'spectator')
+        ORDER BY b.id DESC
+        "#,
+        viewer_id,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_username(user_id: i32, pool: &PgPool) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query!("SELECT username FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
+        .await
+        .map(|row| row.map(|row| row.username))
+}
+
+#[derive(Debug, Clone)]
+pub struct ChapterResult {
+    pub book_id: i32,
+    pub chapter_id: i32,
+    pub title: String,
+    pub points: i32,
+    pub total_points: i32,
+    pub rank: i32,
+}
+
+/// The user's points and standing after every graded chapter, oldest first.
+/// Only their own entry counts, not any extra entries.
+pub async fn chapter_results(
+    user_id: i32,
+    book_ids: &[i32],
+    pool: &PgPool,
+) -> Result<Vec<ChapterResult>, sqlx::Error> {
+    sqlx::query_as!(
+        ChapterResult,
+        r#"
+        WITH chapter_points AS (
+            SELECT
+                c.book_id,
+                c.id AS chapter_id,
+                c.title,
+                c.position,
+                s.user_id,
+                COALESCE(SUM(p.points), 0) AS points
+            FROM chapters AS c
+            JOIN subscriptions AS s ON s.book_id = c.book_id AND NOT (s.role ? 'spectator')
+            LEFT JOIN picks AS p ON p.chapter_id = c.id AND p.user_id = s.user_id AND p.entry_id IS NULL
+            WHERE c.book_id = ANY($2)
+                AND EXISTS (
+                    SELECT 1
+                    FROM picks AS graded
+                    WHERE graded.chapter_id = c.id AND graded.points IS NOT NULL
+                )
+            GROUP BY c.book_id, c.id, c.title, c.position, s.user_id
+        ),
+        cumulative AS (
+            SELECT
+                *,
+                SUM(points) OVER (
+                    PARTITION BY book_id, user_id
+                    ORDER BY position
+                ) AS total_points
+            FROM chapter_points
+        ),
+        ranked AS (
+            SELECT
+                *,
+                RANK() OVER (PARTITION BY chapter_id ORDER BY total_points DESC) AS rank
+            FROM cumulative
+        )
+        SELECT
+            book_id AS "book_id!",
+            chapter_id AS "chapter_id!",
+            title AS "title!",
+            points::INT AS "points!",
+            total_points::INT AS "total_points!",
+            rank::INT AS "rank!"
+        FROM ranked
+        WHERE user_id = $1
+        ORDER BY book_id, position
+        "#,
+        user_id,
+        book_ids
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone)]
+pub struct SpreadResult {
+    pub book_id: i32,
+    pub correct: bool,
+}
+
+/// Every spread the user won or lost, oldest first. Pushes and ungraded
+/// games are left out so they neither count as misses nor break streaks.
+pub async fn spread_results(
+    user_id: i32,
+    book_ids: &[i32],
+    pool: &PgPool,
+) -> Result<Vec<SpreadResult>, sqlx::Error> {
+    sqlx::query_as!(
+        SpreadResult,
+        r#"
+        SELECT
+            p.book_id AS "book_id!",
+            (s.spread ->> 'answer') = (ch.choice #>> '{}') AS "correct!"
+        FROM picks AS p
+        JOIN events AS e ON p.event_id = e.id
+        JOIN chapters AS c ON p.chapter_id = c.id
+        CROSS JOIN LATERAL JSONB_ARRAY_ELEMENTS(e.contents -> 'spread_group') WITH ORDINALITY AS s(spread, idx)
+        JOIN LATERAL JSONB_ARRAY_ELEMENTS(p.choice) WITH ORDINALITY AS ch(choice, idx) ON ch.idx = s.idx
+        WHERE p.user_id = $1
+            AND p.entry_id IS NULL
+            AND p.book_id = ANY($2)
+            AND e.event_type = 'spread_group'
+            AND s.spread ->> 'answer' IN ('home', 'away')
+        ORDER BY c.position, e.id, s.idx
+        "#,
+        user_id,
+        book_ids
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Streak {
+    pub correct: bool,
+    pub length: usize,
+}
+
+/// Counts back from the most recent result until the outcome changes
+pub fn current_streak<'a>(
+    results: impl DoubleEndedIterator<Item = &'a SpreadResult>,
+) -> Option<Streak> {
+    let mut results = results.rev().peekable();
+    let correct = results.peek()?.correct;
+    let length = results
+        .take_while(|result| result.correct == correct)
+        .count();
+
+    Some(Streak { correct, length })
+}
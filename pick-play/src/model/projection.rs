@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+
+use super::event::{ChapterPick, ChapterPickHash, Event, EventContent};
+
+const OUTCOMES: [&str; 3] = ["home", "away", "push"];
+
+#[derive(Debug, Clone)]
+pub struct ProjectionMember {
+    pub user_id: i32,
+    /// Set when the row is one of the member's extra entries
+    pub entry_id: Option<i32>,
+    pub username: String,
+    /// Season points from everything except this chapter
+    pub season_points: i32,
+}
+
+/// Entries that count towards the standings, with their points outside of
+/// `chapter_id`. Points added by admins belong to the member's own entry.
+pub async fn projection_members(
+    book_id: i32,
+    chapter_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<ProjectionMember>, sqlx::Error> {
+    sqlx::query_as!(
+        ProjectionMember,
+        r#"
+        SELECT
+            u.id AS "user_id!",
+            e.id AS "entry_id?",
+            COALESCE(u.username || ' (' || e.name || ')', u.username) AS "username!",
+            (
+                COALESCE((
+                    SELECT SUM(p.points)
+                    FROM picks AS p
+                    WHERE p.book_id = $1
+                        AND p.user_id = u.id
+                        AND p.entry_id IS NOT DISTINCT FROM e.id
+                        AND p.chapter_id <> $2
+                ), 0)
+                + CASE WHEN e.id IS NULL THEN COALESCE((
+                    SELECT SUM(a.points)
+                    FROM added_points AS a
+                    WHERE a.book_id = $1 AND a.user_id = u.id
+                ), 0) ELSE 0 END
+            )::INT AS "season_points!"
+        FROM subscriptions AS s
+        JOIN users AS u ON s.user_id = u.id
+        JOIN LATERAL (
+            SELECT NULL::INT AS id, NULL::TEXT AS name
+            UNION ALL
+            SELECT book_entries.id, book_entries.name
+            FROM book_entries
+            WHERE book_entries.book_id = $1 AND book_entries.user_id = u.id
+        ) AS e ON TRUE
+        WHERE s.book_id = $1
+            AND NOT (s.role ? 'guest')
+            AND NOT (s.role ? 'spectator')
+        ORDER BY u.username, e.name NULLS FIRST
+        "#,
+        book_id,
+        chapter_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// A game in the chapter that has not been graded yet
+#[derive(Debug, Clone)]
+pub struct RemainingGame {
+    pub event_id: i32,
+    pub index: usize,
+    pub home_id: i32,
+    pub away_id: i32,
+    pub home_spread: f64,
+}
+
+impl RemainingGame {
+    /// Form field name for the game's what-if outcome
+    pub fn key(&self) -> String {
+        format!("{}-{}", self.event_id, self.index)
+    }
+}
+
+fn is_graded(answer: Option<&str>) -> bool {
+    answer.is_some_and(|answer| answer != "unpicked")
+}
+
+pub fn remaining_games(events: &[Event]) -> Vec<RemainingGame> {
+    events
+        .iter()
+        .flat_map(|event| match &event.contents.0 {
+            EventContent::SpreadGroup(spreads) => spreads
+                .iter()
+                .enumerate()
+                .filter(|(_, spread)| !is_graded(spread.answer.as_deref()))
+                .map(|(index, spread)| RemainingGame {
+                    event_id: event.id,
+                    index,
+                    home_id: spread.home_id,
+                    away_id: spread.away_id,
+                    home_spread: spread.home_spread,
+                })
+                .collect(),
+            EventContent::UserInput(_) => vec![],
+        })
+        .collect()
+}
+
+/// What a member stands to gain from one undecided pick
+#[derive(Debug, Clone)]
+enum Open {
+    Game {
+        key: (i32, usize),
+        choice: String,
+        wager: i32,
+    },
+    Question {
+        event_id: i32,
+        choice: String,
+        wager: i32,
+    },
+}
+
+impl Open {
+    fn wager(&self) -> i32 {
+        match self {
+            Open::Game { wager, .. } | Open::Question { wager, .. } => *wager,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Projection {
+    pub user_id: i32,
+    pub username: String,
+    pub chapter_points: i32,
+    pub max_chapter_points: i32,
+    pub season_points: i32,
+    pub max_season_points: i32,
+    /// Wins the chapter no matter how the remaining games go
+    pub chapter_clinched: bool,
+    /// Cannot catch the chapter leader even if every remaining pick hits
+    pub chapter_eliminated: bool,
+    pub season_clinched: bool,
+    pub season_eliminated: bool,
+}
+
+struct Card<'a> {
+    member: &'a ProjectionMember,
+    points: i32,
+    open: Vec<Open>,
+}
+
+/// The first and second member's undecided pick on the same game
+type Sides<'a> = (Option<&'a Open>, Option<&'a Open>);
+
+/// Best and worst swing in `first`'s favour over `second` across their
+/// undecided picks. Both play the same games, so a pick on the same side
+/// cancels out instead of counting for each of them independently.
+fn swing(first: &Card, second: &Card) -> (i32, i32) {
+    let mut games: HashMap<(i32, usize), Sides<'_>> = HashMap::new();
+    let mut questions: HashMap<i32, Sides<'_>> = HashMap::new();
+    for (open, is_first) in first
+        .open
+        .iter()
+        .map(|open| (open, true))
+        .chain(second.open.iter().map(|open| (open, false)))
+    {
+        let entry = match open {
+            Open::Game { key, .. } => games.entry(*key).or_default(),
+            Open::Question { event_id, .. } => questions.entry(*event_id).or_default(),
+        };
+        if is_first {
+            entry.0 = Some(open);
+        } else {
+            entry.1 = Some(open);
+        }
+    }
+
+    let mut best = 0;
+    let mut worst = 0;
+    for (first, second) in games.values() {
+        let differentials = OUTCOMES.map(|outcome| {
+            let points = |open: &Option<&Open>| match open {
+                Some(Open::Game { choice, wager, .. }) if choice == outcome => *wager,
+                _ => 0,
+            };
+            points(first) - points(second)
+        });
+        best += differentials.iter().max().copied().unwrap_or(0);
+        worst += differentials.iter().min().copied().unwrap_or(0);
+    }
+    for (first, second) in questions.values() {
+        let first_wager = first.map_or(0, Open::wager);
+        let second_wager = second.map_or(0, Open::wager);
+        let same_answer = match (first, second) {
+            (Some(Open::Question { choice: a, .. }), Some(Open::Question { choice: b, .. })) => {
+                a == b
+            }
+            _ => false,
+        };
+        let differentials = if same_answer {
+            vec![0, first_wager - second_wager]
+        } else {
+            vec![0, first_wager, -second_wager, first_wager - second_wager]
+        };
+        best += differentials.iter().max().copied().unwrap_or(0);
+        worst += differentials.iter().min().copied().unwrap_or(0);
+    }
+
+    (best, worst)
+}
+
+/// Whether `cards[index]` is guaranteed to finish first, and whether it can
+/// no longer finish first. Only decided when one rival is enough to show it,
+/// so a member can be neither clinched nor eliminated even if no outcome of
+/// the remaining games would actually let them win.
+fn race(cards: &[Card], index: usize, base: impl Fn(&Card) -> i32) -> (bool, bool) {
+    let card = &cards[index];
+    let mut clinched = cards.len() > 1;
+    let mut eliminated = false;
+    for (_, rival) in cards.iter().enumerate().filter(|(i, _)| *i != index) {
+        let (best, worst) = swing(card, rival);
+        let lead = base(card) - base(rival);
+        clinched &= lead + worst > 0;
+        eliminated |= lead + best < 0;
+    }
+    (clinched, eliminated)
+}
+
+/// Projects the chapter and season standings with `outcomes` (keyed by
+/// [`RemainingGame::key`]) applied on top of the chapter's current grades
+pub fn project(
+    members: &[ProjectionMember],
+    events: &[Event],
+    picks: &HashMap<ChapterPickHash, ChapterPick>,
+    outcomes: &HashMap<String, String>,
+) -> Vec<Projection> {
+    let cards = members
+        .iter()
+        .map(|member| {
+            let mut card = Card {
+                member,
+                points: 0,
+                open: vec![],
+            };
+            for event in events {
+                let pick = picks.get(&ChapterPickHash {
+                    event_id: event.id,
+                    user_id: member.user_id,
+                    entry_id: member.entry_id,
+                });
+                match (&event.contents.0, pick) {
+                    (
+                        EventContent::SpreadGroup(spreads),
+                        Some(ChapterPick::SpreadGroup { choice, wager, .. }),
+                    ) => {
+                        for (index, ((spread, choice), wager)) in
+                            spreads.iter().zip(choice).zip(wager).enumerate()
+                        {
+                            let answer = spread
+                                .answer
+                                .as_deref()
+                                .filter(|answer| *answer != "unpicked")
+                                .or_else(|| {
+                                    outcomes
+                                        .get(&format!("{}-{index}", event.id))
+                                        .map(String::as_str)
+                                        .filter(|outcome| OUTCOMES.contains(outcome))
+                                });
+                            match answer {
+                                Some(answer) if answer == choice => card.points += wager,
+                                Some(_) => (),
+                                None => card.open.push(Open::Game {
+                                    key: (event.id, index),
+                                    choice: choice.clone(),
+                                    wager: *wager,
+                                }),
+                            }
+                        }
+                    }
+                    (
+                        EventContent::UserInput(input),
+                        Some(ChapterPick::UserInput { choice, wager, .. }),
+                    ) => {
+                        // Grading a chapter before its bonus questions leaves
+                        // them with no acceptable answers
+                        match &input.acceptable_answers {
+                            Some(answers) if !answers.is_empty() => {
+                                if answers.contains(choice) {
+                                    card.points += wager;
+                                }
+                            }
+                            _ => card.open.push(Open::Question {
+                                event_id: event.id,
+                                choice: choice.clone(),
+                                wager: *wager,
+                            }),
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            card
+        })
+        .collect::<Vec<_>>();
+
+    cards
+        .iter()
+        .enumerate()
+        .map(|(index, card)| {
+            let remaining = card.open.iter().map(Open::wager).sum::<i32>();
+            let (chapter_clinched, chapter_eliminated) = race(&cards, index, |card| card.points);
+            let (season_clinched, season_eliminated) = race(&cards, index, |card| {
+                card.member.season_points + card.points
+            });
+            let season_points = card.member.season_points + card.points;
+            Projection {
+                user_id: card.member.user_id,
+                username: card.member.username.clone(),
+                chapter_points: card.points,
+                max_chapter_points: card.points + remaining,
+                season_points,
+                max_season_points: season_points + remaining,
+                chapter_clinched,
+                chapter_eliminated,
+                season_clinched,
+                season_eliminated,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::types::Json;
+
+    use super::*;
+    use crate::model::{event::EventType, spread::Spread, user_input::UserInput};
+
+    fn member(user_id: i32, season_points: i32) -> ProjectionMember {
+        ProjectionMember {
+            user_id,
+            entry_id: None,
+            username: format!("user{user_id}"),
+            season_points,
+        }
+    }
+
+    fn event(id: i32, contents: EventContent) -> Event {
+        Event {
+            id,
+            book_id: 1,
+            chapter_id: 1,
+            is_open: false,
+            event_type: match contents {
+                EventContent::SpreadGroup(_) => EventType::SpreadGroup,
+                EventContent::UserInput(_) => EventType::UserInput,
+            },
+            contents: Json(contents),
+        }
+    }
+
+    fn games(id: i32, answers: &[Option<&str>]) -> Event {
+        event(
+            id,
+            EventContent::SpreadGroup(
+                answers
+                    .iter()
+                    .map(|answer| Spread {
+                        home_id: 1,
+                        away_id: 2,
+                        home_spread: -3.5,
+                        answer: answer.map(str::to_string),
+                        notes: None,
+                    })
+                    .collect(),
+            ),
+        )
+    }
+
+    fn question(id: i32, acceptable_answers: Option<&[&str]>) -> Event {
+        event(
+            id,
+            EventContent::UserInput(UserInput {
+                title: "Bonus".into(),
+                description: None,
+                points: 2,
+                acceptable_answers: acceptable_answers
+                    .map(|answers| answers.iter().map(|answer| answer.to_string()).collect()),
+            }),
+        )
+    }
+
+    fn pick(
+        picks: &mut HashMap<ChapterPickHash, ChapterPick>,
+        event_id: i32,
+        user_id: i32,
+        pick: ChapterPick,
+    ) {
+        picks.insert(
+            ChapterPickHash {
+                event_id,
+                user_id,
+                entry_id: None,
+            },
+            pick,
+        );
+    }
+
+    fn spread_pick(choice: &[&str], wager: &[i32]) -> ChapterPick {
+        ChapterPick::SpreadGroup {
+            choice: choice.iter().map(|choice| choice.to_string()).collect(),
+            wager: wager.to_vec(),
+            points: None,
+        }
+    }
+
+    fn question_pick(choice: &str, wager: i32) -> ChapterPick {
+        ChapterPick::UserInput {
+            choice: choice.into(),
+            wager,
+            points: None,
+        }
+    }
+
+    #[test]
+    fn tied_members_are_neither_clinched_nor_eliminated() {
+        let members = [member(1, 10), member(2, 10)];
+        let events = [games(1, &[Some("home"), Some("away")])];
+        let mut picks = HashMap::new();
+        pick(&mut picks, 1, 1, spread_pick(&["home", "home"], &[2, 1]));
+        pick(&mut picks, 1, 2, spread_pick(&["away", "away"], &[1, 2]));
+
+        let projections = project(&members, &events, &picks, &HashMap::new());
+        for projection in &projections {
+            assert_eq!(projection.chapter_points, 2);
+            assert_eq!(projection.max_chapter_points, 2);
+            assert_eq!(projection.season_points, 12);
+            assert!(!projection.chapter_clinched && !projection.chapter_eliminated);
+            assert!(!projection.season_clinched && !projection.season_eliminated);
+        }
+    }
+
+    #[test]
+    fn same_pending_picks_cancel_out() {
+        let members = [member(1, 0), member(2, 0)];
+        let events = [games(1, &[Some("home"), None])];
+        let mut picks = HashMap::new();
+        pick(&mut picks, 1, 1, spread_pick(&["home", "away"], &[2, 1]));
+        pick(&mut picks, 1, 2, spread_pick(&["away", "away"], &[1, 2]));
+
+        let projections = project(&members, &events, &picks, &HashMap::new());
+        assert_eq!(projections[0].chapter_points, 2);
+        assert_eq!(projections[0].max_chapter_points, 3);
+        assert_eq!(projections[1].chapter_points, 0);
+        assert_eq!(projections[1].max_chapter_points, 2);
+        // Both took the away side, so the second member can only ever close
+        // the gap to a tie
+        assert!(projections[0].chapter_clinched);
+        assert!(projections[1].chapter_eliminated);
+    }
+
+    #[test]
+    fn members_without_remaining_picks() {
+        let members = [member(1, 0), member(2, 0), member(3, 0)];
+        let events = [games(1, &[Some("home"), None])];
+        let mut picks = HashMap::new();
+        pick(&mut picks, 1, 1, spread_pick(&["away", "home"], &[1, 2]));
+        // Picked before the second game was added
+        pick(&mut picks, 1, 3, spread_pick(&["home"], &[1]));
+
+        let projections = project(&members, &events, &picks, &HashMap::new());
+        let [still_open, no_picks, short_picks] = &projections[..] else {
+            panic!("expected a projection for every member");
+        };
+        assert_eq!(
+            (still_open.chapter_points, still_open.max_chapter_points),
+            (0, 2)
+        );
+        assert!(!still_open.chapter_clinched && !still_open.chapter_eliminated);
+
+        assert_eq!(
+            (no_picks.chapter_points, no_picks.max_chapter_points),
+            (0, 0)
+        );
+        assert!(!no_picks.chapter_clinched);
+        assert!(no_picks.chapter_eliminated);
+
+        assert_eq!(
+            (short_picks.chapter_points, short_picks.max_chapter_points),
+            (1, 1)
+        );
+        assert!(!short_picks.chapter_clinched && !short_picks.chapter_eliminated);
+    }
+
+    #[test]
+    fn pending_answers_take_the_what_if_outcome() {
+        let members = [member(1, 0)];
+        let events = [games(1, &[None, None, Some("unpicked")])];
+        let mut picks = HashMap::new();
+        pick(
+            &mut picks,
+            1,
+            1,
+            spread_pick(&["home", "away", "home"], &[3, 2, 1]),
+        );
+        let outcomes = HashMap::from([
+            ("1-0".to_string(), "home".to_string()),
+            ("1-1".to_string(), "sideways".to_string()),
+        ]);
+
+        let projection = &project(&members, &events, &picks, &outcomes)[0];
+        assert_eq!(projection.chapter_points, 3);
+        // The unknown outcome and the unpicked game are still open
+        assert_eq!(projection.max_chapter_points, 6);
+        assert!(!projection.chapter_clinched);
+    }
+
+    #[test]
+    fn ungraded_questions_stay_open() {
+        let members = [member(1, 0), member(2, 0)];
+        let events = [question(1, None), question(2, Some(&["yes"]))];
+        let mut picks = HashMap::new();
+        pick(&mut picks, 1, 1, question_pick("a", 2));
+        pick(&mut picks, 2, 1, question_pick("no", 2));
+        pick(&mut picks, 1, 2, question_pick("a", 2));
+        pick(&mut picks, 2, 2, question_pick("yes", 2));
+
+        let projections = project(&members, &events, &picks, &HashMap::new());
+        assert_eq!(projections[0].chapter_points, 0);
+        assert_eq!(projections[0].max_chapter_points, 2);
+        assert_eq!(projections[1].chapter_points, 2);
+        assert_eq!(projections[1].max_chapter_points, 4);
+        // Same answer on the open question, so the lead can't change
+        assert!(projections[1].chapter_clinched);
+        assert!(projections[0].chapter_eliminated);
+    }
+}
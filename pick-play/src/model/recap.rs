@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, PgPool};
+
+use super::{
+    book::standings_history,
+    chapter::{get_chapter_users, ChapterUser},
+    consensus::Consensus,
+    event::{get_chapter_picks, get_events, ChapterPick, ChapterPickHash, Event, EventContent},
+    team::get_chapter_teams,
+};
+use crate::AppError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecapUser {
+    pub user_id: i32,
+    /// Set when the user is one of the member's extra entries
+    #[serde(default)]
+    pub entry_id: Option<i32>,
+    pub username: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Upset {
+    pub matchup: String,
+    pub covered: String,
+    pub wrong: i32,
+    pub total: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoldPick {
+    pub user: RecapUser,
+    pub matchup: String,
+    pub team: String,
+    pub wager: i32,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mover {
+    pub user: RecapUser,
+    pub previous_rank: i32,
+    pub rank: i32,
+}
+
+impl Mover {
+    pub fn change(&self) -> i32 {
+        self.previous_rank - self.rank
+    }
+}
+
+/// Snapshot of a graded chapter. Names are stored so that the recap reads the
+/// same even if teams or users are renamed later.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Recap {
+    pub winners: Vec<RecapUser>,
+    pub winning_points: i32,
+    pub biggest_upset: Option<Upset>,
+    pub boldest_pick: Option<BoldPick>,
+    pub movers: Vec<Mover>,
+    pub perfect_cards: Vec<RecapUser>,
+    /// Top scorers on the chapter's bonus questions
+    #[serde(default)]
+    pub bonus_leaders: Vec<RecapUser>,
+}
+
+const MAX_MOVERS: usize = 3;
+
+fn matchup(
+    spread: &super::spread::Spread,
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> String {
+    let team = |id| teams.get(&id).map_or("Unknown", |(name, _)| name.as_str());
+    format!(
+        "{} @ {} ({:+})",
+        team(spread.away_id),
+        team(spread.home_id),
+        spread.home_spread
+    )
+}
+
+fn is_perfect(
+    user: &ChapterUser,
+    events: &[Event],
+    picks: &HashMap<ChapterPickHash, ChapterPick>,
+) -> bool {
+    events.iter().all(|event| {
+        let pick = picks.get(&ChapterPickHash {
+            event_id: event.id,
+            user_id: user.user_id,
+            entry_id: user.entry_id,
+        });
+        match (&event.contents.0, pick) {
+            (EventContent::SpreadGroup(spreads), Some(ChapterPick::SpreadGroup { choice, .. })) => {
+                spreads
+                    .iter()
+                    .zip(choice)
+                    .all(|(spread, choice)| match spread.answer.as_deref() {
+                        Some("push") | Some("unpicked") => true,
+                        Some(answer) => answer == choice,
+                        None => false,
+                    })
+            }
+            (EventContent::UserInput(input), Some(ChapterPick::UserInput { choice, .. })) => input
+                .acceptable_answers
+                .as_ref()
+                .is_some_and(|answers| answers.contains(choice)),
+            _ => false,
+        }
+    })
+}
+
+pub fn build_recap(
+    chapter_id: i32,
+    users: &[ChapterUser],
+    events: &[Event],
+    picks: &HashMap<ChapterPickHash, ChapterPick>,
+    teams: &HashMap<i32, (String, Option<String>)>,
+    history: &[super::book::Standing],
+) -> Recap {
+    let recap_user = |user: &ChapterUser| RecapUser {
+        user_id: user.user_id,
+        entry_id: user.entry_id,
+        username: user.username.clone(),
+    };
+
+    let winning_points = users
+        .iter()
+        .map(|user| user.total_points)
+        .max()
+        .unwrap_or(0);
+    let winners = users
+        .iter()
+        .filter(|user| winning_points > 0 && user.total_points == winning_points)
+        .map(recap_user)
+        .collect();
+
+    let mut biggest_upset: Option<Upset> = None;
+    let mut boldest_pick: Option<BoldPick> = None;
+    for event in events {
+        let EventContent::SpreadGroup(spreads) = &event.contents.0 else {
+            continue;
+        };
+        for (index, spread) in spreads.iter().enumerate() {
+            let Some(answer) = spread
+                .answer
+                .as_deref()
+                .filter(|answer| matches!(*answer, "home" | "away"))
+            else {
+                continue;
+            };
+
+            let user_choices = users
+                .iter()
+                .filter_map(|user| {
+                    match picks.get(&ChapterPickHash {
+                        event_id: event.id,
+                        user_id: user.user_id,
+                        entry_id: user.entry_id,
+                    }) {
+                        // Picks stored before a game was added are shorter
+                        // than the spread group
+                        Some(ChapterPick::SpreadGroup { choice, wager, .. }) => {
+                            Some((user, choice.get(index)?.as_str(), *wager.get(index)?))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut consensus = Consensus::default();
+            user_choices
+                .iter()
+                .for_each(|(_, choice, wager)| consensus.add(choice, *wager));
+
+            let wrong = user_choices
+                .iter()
+                .filter(|(_, choice, _)| *choice != answer)
+                .count() as i32;
+            if wrong > biggest_upset.as_ref().map_or(0, |upset| upset.wrong) {
+                let covered = if answer == "home" {
+                    spread.home_id
+                } else {
+                    spread.away_id
+                };
+                biggest_upset = Some(Upset {
+                    matchup: matchup(spread, teams),
+                    covered: teams
+                        .get(&covered)
+                        .map_or("Unknown".to_string(), |(name, _)| name.clone()),
+                    wrong,
+                    total: consensus.total(),
+                });
+            }
+
+            let percent = if answer == "home" {
+                consensus.home_percent()
+            } else {
+                consensus.away_percent()
+            };
+            for (user, _, wager) in user_choices
+                .iter()
+                .filter(|(_, choice, _)| *choice == answer && consensus.is_contrarian(choice))
+            {
+                let bolder = boldest_pick.as_ref().is_none_or(|bold| {
+                    *wager > bold.wager || (*wager == bold.wager && percent < bold.percent)
+                });
+                if bolder {
+                    boldest_pick = Some(BoldPick {
+                        user: recap_user(user),
+                        matchup: matchup(spread, teams),
+                        team: teams
+                            .get(if answer == "home" {
+                                &spread.home_id
+                            } else {
+                                &spread.away_id
+                            })
+                            .map_or("Unknown".to_string(), |(name, _)| name.clone()),
+                        wager: *wager,
+                        percent,
+                    });
+                }
+            }
+        }
+    }
+
+    // History is in chapter order, so the previous chapter is whichever one
+    // was seen last before this chapter
+    let previous_chapter = history
+        .iter()
+        .map(|standing| standing.chapter_id)
+        .take_while(|id| *id != chapter_id)
+        .last();
+    let previous_ranks = history
+        .iter()
+        .filter(|standing| Some(standing.chapter_id) == previous_chapter)
+        .map(|standing| ((standing.user_id, standing.entry_id), standing.rank))
+        .collect::<HashMap<_, _>>();
+    let mut movers = history
+        .iter()
+        .filter(|standing| standing.chapter_id == chapter_id)
+        .filter_map(|standing| {
+            let previous_rank = *previous_ranks.get(&(standing.user_id, standing.entry_id))?;
+            (previous_rank != standing.rank).then(|| Mover {
+                user: RecapUser {
+                    user_id: standing.user_id,
+                    entry_id: standing.entry_id,
+                    username: standing.username.clone(),
+                },
+                previous_rank,
+                rank: standing.rank,
+            })
+        })
+        .collect::<Vec<_>>();
+    movers.sort_by_key(|mover| std::cmp::Reverse(mover.change().abs()));
+    movers.truncate(MAX_MOVERS);
+
+    let perfect_cards = users
+        .iter()
+        .filter(|user| is_perfect(user, events, picks))
+        .map(recap_user)
+        .collect();
+
+    let bonus_points = users
+        .iter()
+        .map(|user| {
+            let points = events
+                .iter()
+                .filter_map(|event| {
+                    match picks.get(&ChapterPickHash {
+                        event_id: event.id,
+                        user_id: user.user_id,
+                        entry_id: user.entry_id,
+                    }) {
+                        Some(ChapterPick::UserInput { points, .. }) => *points,
+                        _ => None,
+                    }
+                })
+                .sum::<i32>();
+            (user, points)
+        })
+        .collect::<Vec<_>>();
+    let top_bonus_points = bonus_points
+        .iter()
+        .map(|(_, points)| *points)
+        .max()
+        .unwrap_or(0);
+    let bonus_leaders = bonus_points
+        .into_iter()
+        .filter(|(_, points)| top_bonus_points > 0 && *points == top_bonus_points)
+        .map(|(user, _)| recap_user(user))
+        .collect();
+
+    Recap {
+        winners,
+        winning_points,
+        biggest_upset,
+        boldest_pick,
+        movers,
+        perfect_cards,
+        bonus_leaders,
+    }
+}
+
+/// Builds the recap from the chapter's current grades and stores it,
+/// replacing any earlier recap for the chapter
+pub async fn generate_recap(
+    chapter_id: i32,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<(), AppError<'_>> {
+    let users = get_chapter_users(book_id, chapter_id, pool).await?;
+    let events = get_events(chapter_id, pool).await?;
+    let picks = get_chapter_picks(chapter_id, pool).await?;
+    let teams = get_chapter_teams(chapter_id, pool).await?;
+    let history = standings_history(book_id, pool).await?;
+
+    let recap = build_recap(chapter_id, &users, &events, &picks, &teams, &history);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO chapter_recaps (chapter_id, book_id, contents)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (chapter_id)
+        DO UPDATE SET contents = EXCLUDED.contents, created_at = CURRENT_TIMESTAMP
+        "#,
+        chapter_id,
+        book_id,
+        Json(&recap) as _
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_recap(chapter_id: i32, pool: &PgPool) -> Result<Option<Recap>, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        SELECT contents AS "contents: Json<Recap>"
+        FROM chapter_recaps
+        WHERE chapter_id = $1
+        "#,
+        chapter_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|row| row.map(|row| row.contents.0))
+}
+
+#[derive(Debug, Clone)]
+pub struct RecapChapter {
+    pub chapter_id: i32,
+    pub title: String,
+}
+
+/// Recaps for the book's published chapters, newest first
+pub async fn book_recaps(book_id: i32, pool: &PgPool) -> Result<Vec<RecapChapter>, sqlx::Error> {
+    sqlx::query_as!(
+        RecapChapter,
+        r#"
+        SELECT c.id AS chapter_id, c.title
+        FROM chapter_recaps AS r
+        JOIN chapters AS c ON r.chapter_id = c.id
+        WHERE r.book_id = $1 AND c.is_visible
+        ORDER BY c.position DESC
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
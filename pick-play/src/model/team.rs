@@ -42,6 +42,39 @@ pub async fn get_chapter_teams(
     .collect::<HashMap<_, _>>())
 }
 
+pub async fn get_book_teams(
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<HashMap<i32, (String, Option<String>)>, sqlx::Error> {
+    Ok(sqlx::query!(
+        r#"
+            SELECT teams.*
+            FROM teams
+            JOIN (
+                WITH spread_cols AS (
+                    SELECT (spread->>'home_id')::INT AS home_id, (spread->>'away_id')::INT AS away_id
+                    FROM (
+                        SELECT jsonb_array_elements(contents->'spread_group') AS spread
+                        FROM public.events
+                        WHERE contents->'spread_group' IS NOT NULL AND book_id = $1
+                    ) AS t
+                )
+                SELECT home_id AS ids
+                FROM spread_cols
+                UNION
+                SELECT away_id
+                FROM spread_cols
+            ) AS cols ON cols.ids=teams.id
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|record| (record.id, (record.name, record.logo)))
+    .collect::<HashMap<_, _>>())
+}
+
 pub async fn get(team_id: i32, pool: &PgPool) -> Result<Option<Team>, sqlx::Error> {
     sqlx::query_as!(
         Team,
@@ -75,3 +108,107 @@ pub async fn search(
     .fetch_all(pool)
     .await
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtsResult {
+    Cover,
+    Push,
+    Loss,
+}
+
+#[derive(Debug, Clone)]
+pub struct TeamGame {
+    pub chapter_id: i32,
+    pub chapter_title: String,
+    pub opponent_id: i32,
+    pub opponent_name: String,
+    pub is_home: bool,
+    pub home_spread: f64,
+    pub answer: Option<String>,
+    pub times_picked: i32,
+    pub total_picks: i32,
+}
+
+impl TeamGame {
+    /// The spread from this team's point of view
+    pub fn spread(&self) -> f64 {
+        if self.is_home {
+            self.home_spread
+        } else {
+            -self.home_spread
+        }
+    }
+
+    pub fn result(&self) -> Option<AtsResult> {
+        let side = if self.is_home { "home" } else { "away" };
+        match self.answer.as_deref()? {
+            "push" => Some(AtsResult::Push),
+            "unpicked" => None,
+            answer if answer == side => Some(AtsResult::Cover),
+            _ => Some(AtsResult::Loss),
+        }
+    }
+}
+
+/// Every game the team played in the book's published, closed chapters, in
+/// chapter order
+pub async fn book_team_games(
+    team_id: i32,
+    book_id: i32,
+    pool: &PgPool,
+) -> Result<Vec<TeamGame>, sqlx::Error> {
+    sqlx::query_as!(
+        TeamGame,
+        r#"
+        WITH games AS (
+            SELECT
+                c.id AS chapter_id,
+                c.title AS chapter_title,
+                c.position,
+                e.id AS event_id,
+                s.idx,
+                (s.spread ->> 'home_id')::INT = $1 AS is_home,
+                CASE
+                    WHEN (s.spread ->> 'home_id')::INT = $1 THEN (s.spread ->> 'away_id')::INT
+                    ELSE (s.spread ->> 'home_id')::INT
+                END AS opponent_id,
+                (s.spread ->> 'home_spread')::FLOAT8 AS home_spread,
+                s.spread ->> 'answer' AS answer
+            FROM events AS e
+            JOIN chapters AS c ON e.chapter_id = c.id
+            CROSS JOIN LATERAL JSONB_ARRAY_ELEMENTS(e.contents -> 'spread_group') WITH ORDINALITY AS s(spread, idx)
+            WHERE e.book_id = $2
+                AND e.event_type = 'spread_group'
+                AND c.is_visible
+                AND NOT c.is_open
+                AND $1 IN ((s.spread ->> 'home_id')::INT, (s.spread ->> 'away_id')::INT)
+        )
+        SELECT
+            g.chapter_id AS "chapter_id!",
+            g.chapter_title AS "chapter_title!",
+            g.opponent_id AS "opponent_id!",
+            t.name AS "opponent_name!",
+            g.is_home AS "is_home!",
+            g.home_spread AS "home_spread!",
+            g.answer,
+            (
+                SELECT COUNT(*)
+                FROM picks AS p
+                WHERE p.event_id = g.event_id
+                    AND p.choice ->> (g.idx - 1)::INT = CASE WHEN g.is_home THEN 'home' ELSE 'away' END
+            )::INT AS "times_picked!",
+            (
+                SELECT COUNT(*)
+                FROM picks AS p
+                WHERE p.event_id = g.event_id
+            )::INT AS "total_picks!"
+        FROM games AS g
+        JOIN teams AS t ON g.opponent_id = t.id
+        ORDER BY g.position, g.event_id, g.idx
+        "#,
+        team_id,
+        book_id
+    )
+    .fetch_all(pool)
+    .await
+}
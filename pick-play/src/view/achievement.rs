@@ -0,0 +1,56 @@
+use itertools::Itertools;
+
+use crate::model::achievement::{Achievement, Badge};
+
+/// Compact badge icons for a leaderboard row
+pub fn badges(badges: &[(Badge, usize)]) -> maud::Markup {
+    maud::html! {
+        @for (badge, count) in badges {
+            span class="ml-1 text-xs whitespace-nowrap" title=(badge.label()) {
+                (badge.icon())
+                @if *count > 1 { "×" (count) }
+            }
+        }
+    }
+}
+
+pub fn trophy_case(achievements: &[Achievement]) -> maud::Markup {
+    let members = achievements
+        .iter()
+        .into_group_map_by(|achievement| achievement.username.as_str())
+        .into_iter()
+        .sorted_by_key(|(username, _)| *username)
+        .map(|(username, achievements)| {
+            let badges = achievements
+                .into_iter()
+                .into_group_map_by(|achievement| achievement.badge);
+            (username, badges)
+        })
+        .collect::<Vec<_>>();
+
+    maud::html! {
+        div class="w-full p-3 mt-2 bg-white border border-gray-300 rounded-lg shadow-lg" {
+            @if members.is_empty() {
+                p class="text-gray-500" { "No badges earned yet" }
+            }
+            @for (username, badges) in &members {
+                div class="py-2 border-b border-gray-100 last:border-b-0" {
+                    p class="font-bold" { (username) }
+                    div class="flex flex-wrap gap-2" {
+                        @for badge in Badge::ALL {
+                            @if let Some(chapters) = badges.get(&badge) {
+                                div class="px-2 py-1 text-sm rounded bg-amber-50" title=(badge.description()) {
+                                    (badge.icon()) " " (badge.label())
+                                    @if chapters.len() > 1 { " ×" (chapters.len()) }
+                                    span class="block text-xs text-gray-500" {
+                                        (chapters.iter().map(|achievement| achievement.chapter_title.as_str()).join(", "))
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -3,8 +3,13 @@ use std::iter::Peekable;
 use crate::{
     auth::BackendUser,
     model::{
-        book::{BookMember, BookSubscription},
-        chapter::ChapterStats,
+        book::{BookCapability, BookMember, BookRole, BookSubscription},
+        chapter::{Chapter, ChapterStats},
+        group::BookGroup,
+        invite::BookInvite,
+        join_request::JoinRequest,
+        managed::ManagedUser,
+        missed_pick::MissedPickPolicy,
     },
 };
 
@@ -13,6 +18,9 @@ pub fn m<'a, I>(
     book_subscription: &BookSubscription,
     unpublished_chapters: Peekable<I>,
     members: &[BookMember],
+    invites: &[BookInvite],
+    join_requests: &[JoinRequest],
+    groups: &[BookGroup],
 ) -> maud::Markup
 where
     I: Iterator<Item = &'a ChapterStats>,
@@ -21,7 +29,7 @@ where
         &user.username,
         Some(format!("{} - Admin", book_subscription.name).as_str()),
         None,
-        None,
+        Some(crate::view::alertify()),
         Some(maud::html! {
             p {
                 a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
@@ -30,11 +38,26 @@ where
             }
         }),
         Some(maud::html! {
+            @let role = &book_subscription.role;
             div class="flex flex-col items-center justify-center" {
-                (create_chapter_button())
-                (chapter_management_section(book_subscription.id, unpublished_chapters))
-                (danger_zone())
-                (member_management_table(user, members))
+                @if role.has_capability(BookCapability::ManageChapters) {
+                    (create_chapter_button())
+                    (chapter_management_section(book_subscription.id, unpublished_chapters))
+                    div hx-get="missed-pick-policy" hx-trigger="load" hx-swap="outerHTML" {}
+                }
+                @if role.is_admin() {
+                    (danger_zone())
+                }
+                @if role.has_capability(BookCapability::ManageMembers) {
+                    (member_management_table(user, role, members))
+                    (crate::view::join_request::pending_queue(book_subscription.id, join_requests))
+                    (invite_section(invites))
+                    div hx-get="managed-users" hx-trigger="load" hx-swap="outerHTML" {}
+                    (group_section(user, members, groups))
+                }
+                @if role.has_capability(BookCapability::AdjustPoints) {
+                    div hx-get="add-points" hx-trigger="load" hx-swap="outerHTML" {}
+                }
             }
         }),
         None,
@@ -84,46 +107,56 @@ fn danger_zone() -> maud::Markup {
     }
 }
 
-fn member_management_table(user: &BackendUser, members: &[BookMember]) -> maud::Markup {
+fn member_management_table(
+    user: &BackendUser,
+    viewer_role: &BookRole,
+    members: &[BookMember],
+) -> maud::Markup {
     maud::html! {
         div class="relative mt-5 overflow-x-auto rounded-lg" {
             table class="w-full text-sm text-left text-gray-500 rtl:text-right" {
-                (table_header())
-                (table_body(user, members))
+                (table_header(viewer_role.is_admin()))
+                (table_body(user, viewer_role, members))
                 (table_footer())
             }
         }
     }
 }
 
-fn table_header() -> maud::Markup {
+fn table_header(show_capabilities: bool) -> maud::Markup {
     maud::html! {
         thead class="text-xs text-gray-700 uppercase bg-gray-100" {
             tr {
                 th scope="col" class="px-6 py-3 rounded-s-lg" { "username" }
                 th scope="col" class="px-6 py-3" { "status" }
+                @if show_capabilities {
+                    th scope="col" class="px-6 py-3" { "capabilities" }
+                }
                 th scope="col" class="px-6 py-3 rounded-e-lg" { "action" }
             }
         }
     }
 }
 
-fn table_body(user: &BackendUser, members: &[BookMember]) -> maud::Markup {
+fn table_body(user: &BackendUser, viewer_role: &BookRole, members: &[BookMember]) -> maud::Markup {
     maud::html! {
         tbody {
-            (admin_row(&user.username))
+            (admin_row(&user.username, viewer_role))
             @for member in members {
-                (member_row(member))
+                (member_row(member, viewer_role.is_admin()))
             }
         }
     }
 }
 
-fn admin_row(username: &str) -> maud::Markup {
+fn admin_row(username: &str, viewer_role: &BookRole) -> maud::Markup {
     maud::html! {
         tr class="bg-white" {
             td scope="row" class="px-6 py-4 font-medium text-gray-900 whitespace-nowrap" { (username) }
-            td class="px-6 py-4" { "admin" }
+            td class="px-6 py-4" { (viewer_role.label()) }
+            @if viewer_role.is_admin() {
+                td class="px-6 py-4" { "all" }
+            }
             td class="px-6 py-4" {
                 button {
                     "Heavy is The Head" br;
@@ -134,11 +167,20 @@ fn admin_row(username: &str) -> maud::Markup {
     }
 }
 
-fn member_row(member: &BookMember) -> maud::Markup {
+pub fn member_row(member: &BookMember, show_capabilities: bool) -> maud::Markup {
     maud::html! {
         tr class="bg-white" hx-target="this" {
             td class="px-6 py-4 font-medium text-gray-900 whitespace-nowrap" { (member.username) }
-            td class="px-6 py-4" { (member.role) }
+            td class="px-6 py-4" { (member.role.0.label()) }
+            @if show_capabilities {
+                td class="px-6 py-4" {
+                    @if matches!(member.role.0, BookRole::Participant | BookRole::Moderator { .. }) {
+                        @for capability in BookCapability::ALL {
+                            (capability_toggle(member.id, capability, member.role.0.has_capability(capability)))
+                        }
+                    }
+                }
+            }
             td class="px-6 py-4" {
                 button
                     hx-post="remove-user"
@@ -146,6 +188,268 @@ fn member_row(member: &BookMember) -> maud::Markup {
                     class="px-2 py-2 mt-1 font-bold text-white bg-orange-600 rounded hover:bg-orange-700" {
                     "Remove"
                 }
+                @let is_spectator = member.role.0 == BookRole::Spectator;
+                @if is_spectator || matches!(member.role.0, BookRole::Participant | BookRole::Moderator { .. }) {
+                    button
+                        hx-post={"spectator?toggle="(!is_spectator)}
+                        hx-vals={r#"{"user_id":""#(member.id)r#"","username":""#(member.username)r#""}"#}
+                        hx-swap="outerHTML"
+                        class="px-2 py-2 mt-1 ml-1 font-bold text-white bg-gray-500 rounded hover:bg-gray-600" {
+                        @if is_spectator { "Make Player" } @else { "Make Spectator" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn capability_toggle(user_id: i32, capability: BookCapability, enabled: bool) -> maud::Markup {
+    maud::html! {
+        label class="inline-flex items-center mr-2 cursor-pointer" {
+            input type="checkbox" class="mr-1" checked[enabled]
+                hx-post={"capability?toggle="(!enabled)}
+                hx-vals={r#"{"user_id":""#(user_id)r#"","capability":""#(capability.key())r#""}"#}
+                hx-trigger="change"
+                hx-target="closest label"
+                hx-swap="outerHTML";
+            (capability.label())
+        }
+    }
+}
+
+fn invite_section(invites: &[BookInvite]) -> maud::Markup {
+    maud::html! {
+        div class="flex justify-center my-6" {
+            fieldset class="p-3 border border-green-600" {
+                legend class="ml-3" { "Invite Links" }
+                form hx-post="invite" hx-target="next tbody" hx-swap="afterbegin" class="flex flex-wrap items-center justify-center gap-2" {
+                    input type="number" name="expires_in_days" min="1" placeholder="Expires in (days)" class="border border-green-300";
+                    input type="number" name="max_uses" min="1" placeholder="Max uses" class="border border-green-300";
+                    button type="submit" class="px-2 py-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                        "Create Invite"
+                    }
+                }
+                table class="w-full mt-3 text-sm text-left text-gray-500" {
+                    thead class="text-xs text-gray-700 uppercase bg-gray-100" {
+                        tr {
+                            th scope="col" class="px-3 py-2" { "code" }
+                            th scope="col" class="px-3 py-2" { "uses" }
+                            th scope="col" class="px-3 py-2" { "expires" }
+                            th scope="col" class="px-3 py-2" { "action" }
+                        }
+                    }
+                    tbody {
+                        @for invite in invites {
+                            (invite_row(invite))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn invite_row(invite: &BookInvite) -> maud::Markup {
+    maud::html! {
+        tr.line-through[!invite.is_active] class="bg-white" hx-target="this" {
+            td class="px-3 py-2 font-mono text-gray-900" {
+                a href={"/join/"(invite.code)} class="text-blue-400 hover:underline" { (invite.code) }
+            }
+            td class="px-3 py-2" {
+                (invite.uses)
+                @if let Some(max_uses) = invite.max_uses {
+                    " / " (max_uses)
+                }
+            }
+            td class="px-3 py-2" { (invite.expires_at.as_deref().unwrap_or("Never")) }
+            td class="px-3 py-2" {
+                button
+                    onclick={"navigator.clipboard.writeText(window.location.origin + '/join/"(invite.code)"')"}
+                    class="px-2 py-1 mr-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                    "Copy Link"
+                }
+                button
+                    hx-delete="invite"
+                    hx-vals={r#"{"code":""#(invite.code)r#""}"#}
+                    hx-swap="outerHTML"
+                    class="px-2 py-1 font-bold text-white bg-orange-600 rounded hover:bg-orange-700" {
+                    "Delete"
+                }
+            }
+        }
+    }
+}
+
+/// Members without a login, whose picks are made by the member that owns
+/// them, and how many entries each member may play
+pub fn managed_section(
+    user: &BackendUser,
+    members: &[BookMember],
+    managed: &[ManagedUser],
+    max_entries: i32,
+) -> maud::Markup {
+    maud::html! {
+        div class="flex justify-center my-6" {
+            fieldset class="p-3 border border-green-600" {
+                legend class="ml-3" { "Managed Members" }
+                form hx-post="max-entries" hx-swap="none" class="flex items-center justify-center gap-2 mb-3" {
+                    label for="max-entries" { "Entries per member" }
+                    input id="max-entries" type="number" name="max_entries" min="1" value=(max_entries) required class="w-16 border border-green-300";
+                    button type="submit" class="px-2 py-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                        "Save"
+                    }
+                }
+                form hx-post="managed-users" hx-target="next tbody" hx-swap="beforeend" class="flex flex-wrap items-center justify-center gap-2" {
+                    input type="text" name="username" maxlength="30" placeholder="Name" required class="border border-green-300";
+                    select name="owner_id" required class="border border-green-300" {
+                        option value=(user.id) { (user.username) }
+                        @for member in members.iter().filter(|member| !managed.iter().any(|managed| managed.id == member.id)) {
+                            option value=(member.id) { (member.username) }
+                        }
+                    }
+                    button type="submit" class="px-2 py-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                        "Add Managed Member"
+                    }
+                }
+                table class="w-full mt-3 text-sm text-left text-gray-500" {
+                    thead class="text-xs text-gray-700 uppercase bg-gray-100" {
+                        tr {
+                            th scope="col" class="px-3 py-2" { "name" }
+                            th scope="col" class="px-3 py-2" { "picks made by" }
+                        }
+                    }
+                    tbody {
+                        @for managed in managed {
+                            (managed_row(managed))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn managed_row(managed: &ManagedUser) -> maud::Markup {
+    maud::html! {
+        tr class="bg-white" {
+            td class="px-3 py-2 font-medium text-gray-900" { (managed.username) }
+            td class="px-3 py-2" { (managed.owner_username) }
+        }
+    }
+}
+
+fn group_section(user: &BackendUser, members: &[BookMember], groups: &[BookGroup]) -> maud::Markup {
+    maud::html! {
+        div class="flex justify-center my-6" {
+            fieldset class="p-3 border border-green-600" {
+                legend class="ml-3" { "Groups" }
+                form hx-post="group" hx-target="#group-list" hx-swap="outerHTML" class="flex flex-wrap items-center justify-center gap-2" {
+                    input type="text" name="name" list="group-names" maxlength="30" placeholder="Group name" required class="border border-green-300";
+                    datalist id="group-names" {
+                        @for group in groups {
+                            option value=(group.name) {}
+                        }
+                    }
+                    select name="user_id" required class="border border-green-300" {
+                        option value=(user.id) { (user.username) }
+                        @for member in members {
+                            option value=(member.id) { (member.username) }
+                        }
+                    }
+                    button type="submit" class="px-2 py-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                        "Add to Group"
+                    }
+                }
+                (group_list(groups))
+            }
+        }
+    }
+}
+
+pub fn group_list(groups: &[BookGroup]) -> maud::Markup {
+    maud::html! {
+        div id="group-list" class="mt-3" {
+            @if groups.is_empty() {
+                p class="text-gray-500" { "No groups yet" }
+            }
+            @for group in groups {
+                div class="mb-2" {
+                    h3 class="font-bold" { (group.name) }
+                    ul class="flex flex-wrap gap-2" {
+                        @for (user_id, username) in &group.members {
+                            li class="px-2 py-1 bg-gray-100 rounded" {
+                                (username)
+                                button
+                                    hx-delete="group"
+                                    hx-vals={r#"{"name":""#(group.name)r#"","user_id":""#(user_id)r#""}"#}
+                                    hx-target="#group-list"
+                                    hx-swap="outerHTML"
+                                    class="ml-1 text-red-500 hover:text-red-700" {
+                                    "✕"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn missed_pick_policy_form(policy: MissedPickPolicy) -> maud::Markup {
+    maud::html! {
+        div class="flex justify-center my-6" {
+            fieldset class="p-3 border border-green-600" {
+                legend class="ml-3" { "Missed Picks" }
+                p class="mb-2 text-sm text-gray-500" { "Applied to members without picks when a chapter locks" }
+                form hx-post="missed-pick-policy" hx-swap="none" class="flex flex-col gap-1" {
+                    @for option in MissedPickPolicy::ALL {
+                        label class="flex items-start gap-2" {
+                            input type="radio" name="policy" value=(option.key()) checked[option == policy] required;
+                            span {
+                                span class="font-medium" { (option.label()) }
+                                br;
+                                span class="text-xs text-gray-500" { (option.description()) }
+                            }
+                        }
+                    }
+                    button type="submit" class="self-center px-2 py-1 mt-2 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                        "Save"
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn add_points_form(
+    user: &BackendUser,
+    members: &[BookMember],
+    chapters: &[Chapter],
+) -> maud::Markup {
+    maud::html! {
+        div class="flex justify-center my-6" {
+            fieldset class="p-3 border border-orange-600" {
+                legend class="ml-3" { "Adjust Points" }
+                form hx-post="add-points" hx-swap="none" hx-on--after-request="if (event.detail.successful) this.reset()" class="flex flex-col items-center gap-2" {
+                    select name="user_id" required class="border border-green-300" {
+                        option value=(user.id) { (user.username) }
+                        @for member in members {
+                            option value=(member.id) { (member.username) }
+                        }
+                    }
+                    input type="number" name="points" placeholder="Points" required class="border border-green-300";
+                    input type="text" name="reason" placeholder="Reason" required class="border border-green-300";
+                    select name="chapter_id" class="border border-green-300" {
+                        option value="" { "No chapter" }
+                        @for chapter in chapters {
+                            option value=(chapter.chapter_id) { (chapter.title) }
+                        }
+                    }
+                    button type="submit" class="px-2 py-1 font-bold text-white bg-orange-600 rounded hover:bg-orange-700" {
+                        "Add Points"
+                    }
+                }
             }
         }
     }
@@ -195,19 +499,13 @@ pub fn user_search_results(
     )
 }
 
-pub fn new_member_row(user_id: i32, username: &str) -> maud::Markup {
-    maud::html! {
-        tr class="bg-white" hx-target="this" {
-            td class="px-6 py-4 font-medium text-gray-900 whitespace-nowrap" { (username) }
-            td class="px-6 py-4" { "participant" }
-            td class="px-6 py-4" {
-                button
-                    hx-post="remove-user"
-                    hx-vals={r#"{"user_id":""#(user_id)r#""}"#}
-                    class="px-2 py-2 mt-1 font-bold text-white bg-orange-600 rounded hover:bg-orange-700" {
-                    "Remove"
-                }
-            }
-        }
-    }
+pub fn new_member_row(user_id: i32, username: &str, show_capabilities: bool) -> maud::Markup {
+    member_row(
+        &BookMember {
+            id: user_id,
+            username: username.to_string(),
+            role: sqlx::types::Json(BookRole::Participant),
+        },
+        show_capabilities,
+    )
 }
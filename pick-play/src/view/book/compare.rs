@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::model::{
+    book::BookSubscription,
+    compare::{Disagreement, HeadToHead, Matchup, Winner},
+};
+
+pub fn m(
+    username: &str,
+    book_subscription: &BookSubscription,
+    members: &[(i32, String)],
+    [first, second]: [Option<&(i32, String)>; 2],
+    comparison: Option<&HeadToHead>,
+    chapters: &HashMap<i32, String>,
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> maud::Markup {
+    crate::view::authenticated(
+        username,
+        Some(format!("{} - Compare", book_subscription.name).as_str()),
+        None,
+        None,
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a href="." class="text-blue-400 hover:underline" { (book_subscription.name) } " > "
+                a {"Compare"}
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center gap-4" {
+                h1 class="text-4xl font-extrabold" { "Head to Head" }
+                form method="get" class="flex flex-wrap items-center justify-center gap-2" {
+                    (member_select("first", members, first))
+                    span class="font-bold" { "vs" }
+                    (member_select("second", members, second))
+                    button type="submit" class="px-2 py-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                        "Compare"
+                    }
+                }
+                @if let (Some((_, first_name)), Some((_, second_name)), Some(comparison)) = (first, second, comparison) {
+                    (summary(first_name, second_name, comparison))
+                    (differential_table(first_name, second_name, comparison, chapters))
+                    (disagreement_table(first_name, second_name, comparison, chapters, teams))
+                } @else {
+                    p class="text-gray-500" { "Pick two different members to compare" }
+                }
+            }
+        }),
+        None,
+    )
+}
+
+fn member_select(
+    name: &str,
+    members: &[(i32, String)],
+    selected: Option<&(i32, String)>,
+) -> maud::Markup {
+    let selected = selected.map(|(id, _)| *id);
+    maud::html! {
+        select name=(name) required class="border border-green-300 rounded" {
+            option value="" selected[selected.is_none()] { "Choose a member" }
+            @for (id, username) in members {
+                option value=(id) selected[selected == Some(*id)] { (username) }
+            }
+        }
+    }
+}
+
+fn summary(first_name: &str, second_name: &str, comparison: &HeadToHead) -> maud::Markup {
+    let (first_wins, second_wins) =
+        comparison
+            .disagreements
+            .iter()
+            .fold(
+                (0, 0),
+                |(first_wins, second_wins), disagreement| match disagreement.winner {
+                    Some(Winner::First) => (first_wins + 1, second_wins),
+                    Some(Winner::Second) => (first_wins, second_wins + 1),
+                    _ => (first_wins, second_wins),
+                },
+            );
+
+    maud::html! {
+        div class="flex flex-wrap justify-center gap-4" {
+            div class="flex flex-col items-center p-3 bg-white border border-gray-200 rounded-lg shadow-sm" {
+                span class="text-xs text-gray-500 uppercase" { "Agreement" }
+                span class="text-lg font-bold" {
+                    @if let Some(rate) = comparison.agreement_rate() {
+                        (format!("{rate:.0}%"))
+                    } @else {
+                        "-"
+                    }
+                }
+                span class="text-xs text-gray-500" { (comparison.agreements) " of " (comparison.compared) " picks" }
+            }
+            div class="flex flex-col items-center p-3 bg-white border border-gray-200 rounded-lg shadow-sm" {
+                span class="text-xs text-gray-500 uppercase" { "When they differ" }
+                span class="text-lg font-bold" { (first_name) " " (first_wins) " - " (second_wins) " " (second_name) }
+            }
+        }
+    }
+}
+
+fn differential_table(
+    first_name: &str,
+    second_name: &str,
+    comparison: &HeadToHead,
+    chapters: &HashMap<i32, String>,
+) -> maud::Markup {
+    maud::html! {
+        table class="w-full max-w-xl text-sm text-left text-gray-500" {
+            thead class="text-xs text-gray-700 uppercase bg-gray-100" {
+                tr {
+                    th scope="col" class="px-3 py-2" { "Chapter" }
+                    th scope="col" class="px-3 py-2" { (first_name) }
+                    th scope="col" class="px-3 py-2" { (second_name) }
+                    th scope="col" class="px-3 py-2" { "Running +/-" }
+                }
+            }
+            tbody {
+                @for chapter in &comparison.chapters {
+                    tr class="bg-white" {
+                        td class="px-3 py-2" { (chapters.get(&chapter.chapter_id).map_or("", String::as_str)) }
+                        td class="px-3 py-2" { (chapter.first_points) }
+                        td class="px-3 py-2" { (chapter.second_points) }
+                        td.text-green-600[chapter.running_differential > 0].text-red-600[chapter.running_differential < 0] class="px-3 py-2 font-bold" {
+                            (format!("{:+}", chapter.running_differential))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn disagreement_table(
+    first_name: &str,
+    second_name: &str,
+    comparison: &HeadToHead,
+    chapters: &HashMap<i32, String>,
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> maud::Markup {
+    let team = |id: &i32| teams.get(id).map_or("Unknown", |(name, _)| name.as_str());
+
+    maud::html! {
+        table class="w-full max-w-3xl text-sm text-left text-gray-500" {
+            thead class="text-xs text-gray-700 uppercase bg-gray-100" {
+                tr {
+                    th scope="col" class="px-3 py-2" { "Chapter" }
+                    th scope="col" class="px-3 py-2" { "Pick" }
+                    th scope="col" class="px-3 py-2" { (first_name) }
+                    th scope="col" class="px-3 py-2" { (second_name) }
+                }
+            }
+            tbody {
+                @if comparison.disagreements.is_empty() {
+                    tr class="bg-white" {
+                        td colspan="4" class="px-3 py-2 text-center" { "They have never picked differently" }
+                    }
+                }
+                @for disagreement in &comparison.disagreements {
+                    @let Disagreement { chapter_id, matchup, first_choice, second_choice, winner } = disagreement;
+                    @let (first_choice, second_choice) = match matchup {
+                        Matchup::Spread { home_id, away_id, .. } => {
+                            let side = |choice: &str| if choice == "home" { team(home_id) } else { team(away_id) };
+                            (side(first_choice).to_string(), side(second_choice).to_string())
+                        }
+                        Matchup::Question { .. } => (first_choice.clone(), second_choice.clone()),
+                    };
+                    tr class="bg-white" {
+                        td class="px-3 py-2" { (chapters.get(chapter_id).map_or("", String::as_str)) }
+                        td class="px-3 py-2" {
+                            @match matchup {
+                                Matchup::Spread { home_id, away_id, home_spread } => {
+                                    (team(away_id)) " @ " (team(home_id)) " (" (format!("{home_spread:+}")) ")"
+                                }
+                                Matchup::Question { title } => { (title) }
+                            }
+                        }
+                        td.font-bold[*winner == Some(Winner::First)].text-green-600[*winner == Some(Winner::First)] class="px-3 py-2" { (first_choice) }
+                        td.font-bold[*winner == Some(Winner::Second)].text-green-600[*winner == Some(Winner::Second)] class="px-3 py-2" { (second_choice) }
+                    }
+                }
+            }
+        }
+    }
+}
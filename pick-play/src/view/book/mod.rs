@@ -1,2 +1,4 @@
 pub mod admin;
-pub mod page;
\ No newline at end of file
+pub mod compare;
+pub mod page;
+pub mod race;
\ No newline at end of file
@@ -1,7 +1,7 @@
 use crate::{
     auth::BackendUser,
     model::{
-        book::{BookRole, BookSubscription},
+        book::{BookCapability, BookRole, BookSubscription},
         chapter::ChapterStats,
     },
     view::chapter::list as chapter_list,
@@ -26,7 +26,7 @@ pub fn m(
         }),
         Some(maud::html! {
             h1 class="text-4xl font-extrabold" {(book_subscription.name)}
-            @if book_subscription.role == BookRole::Admin {
+            @if book_subscription.role.has_any_capability() {
                 a href="admin/" {
                     button class="fixed z-50 px-3 py-2 text-sm font-bold text-white transition-colors bg-orange-600 rounded-full shadow-lg bottom-4 right-4 hover:bg-orange-700" {
                         "Admin"
@@ -45,10 +45,41 @@ pub fn m(
                 }
             }
 
+            div class="flex items-center justify-center w-full" {
+                details class="relative w-auto max-w-full" {
+                    summary class="p-3 my-1 align-middle bg-green-500 rounded-lg shadow-md cursor-pointer select-none" {
+                        "Player Stats"
+                    }
+                    div hx-get="stats" hx-trigger="toggle once from:closest details" hx-swap="outerHTML" class="w-full mt-2" {
+                        "Loading..."
+                    }
+                }
+                details class="relative w-auto ml-2" {
+                    summary class="p-3 my-1 align-middle bg-green-500 rounded-lg shadow-md cursor-pointer select-none" {
+                        "Recaps"
+                    }
+                    div hx-get="recaps" hx-trigger="toggle once from:closest details" hx-swap="outerHTML" {
+                        "Loading..."
+                    }
+                }
+                details class="relative w-auto ml-2" {
+                    summary class="p-3 my-1 align-middle bg-green-500 rounded-lg shadow-md cursor-pointer select-none" {
+                        "Trophy Case"
+                    }
+                    div hx-get="trophies" hx-trigger="toggle once from:closest details" hx-swap="outerHTML" {
+                        "Loading..."
+                    }
+                }
+                a href="race" class="p-3 my-1 ml-2 align-middle bg-green-500 rounded-lg shadow-md" { "Race" }
+                a href="compare" class="p-3 my-1 ml-2 align-middle bg-green-500 rounded-lg shadow-md" { "Compare" }
+            }
+
             @if let Some(guest_chapters) = guest_chapters {
                 (chapter_list::m(book_subscription.id, chapters.iter().filter(|c| c.is_visible && guest_chapters.contains(&c.id)).peekable(), None))
-            } @else if book_subscription.role == BookRole::Admin {
+            } @else if book_subscription.role.has_capability(BookCapability::ManageChapters) {
                 (chapter_list::m(book_subscription.id, chapters.iter().peekable(), None))
+            } @else if book_subscription.role == BookRole::Spectator {
+                (chapter_list::m(book_subscription.id, chapters.iter().filter(|c| c.is_visible && !c.is_open).peekable(), Some("No closed chapters yet")))
             }   @else {
                 (chapter_list::m(book_subscription.id, chapters.iter().filter(|c| c.is_visible).peekable(), None))
             }
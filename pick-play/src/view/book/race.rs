@@ -0,0 +1,148 @@
+use itertools::Itertools;
+
+use crate::{
+    auth::BackendUser,
+    model::book::{BookSubscription, Standing},
+};
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 320.0;
+const PADDING: f64 = 40.0;
+
+struct Series<'a> {
+    user_id: i32,
+    entry_id: Option<i32>,
+    username: &'a str,
+    points: Vec<i32>,
+    ranks: Vec<i32>,
+}
+
+pub fn m(
+    user: &BackendUser,
+    book_subscription: &BookSubscription,
+    history: &[Standing],
+) -> maud::Markup {
+    let chapters = history
+        .iter()
+        .map(|standing| (standing.chapter_id, standing.chapter_title.as_str()))
+        .unique()
+        .collect::<Vec<_>>();
+
+    let mut series = history
+        .iter()
+        .into_group_map_by(|standing| (standing.user_id, standing.entry_id))
+        .into_iter()
+        .map(|((user_id, entry_id), standings)| Series {
+            user_id,
+            entry_id,
+            username: &standings[0].username,
+            points: standings
+                .iter()
+                .map(|standing| standing.total_points)
+                .collect(),
+            ranks: standings.iter().map(|standing| standing.rank).collect(),
+        })
+        .collect::<Vec<_>>();
+    // Draw the viewer last so their line sits on top
+    series.sort_by_key(|series| {
+        (
+            series.user_id == user.id && series.entry_id.is_none(),
+            series.username.to_string(),
+        )
+    });
+
+    crate::view::authenticated(
+        &user.username,
+        Some(format!("{} - Race", book_subscription.name).as_str()),
+        None,
+        None,
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a href="." class="text-blue-400 hover:underline" { (book_subscription.name) } " > "
+                a {"Race"}
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center gap-6" {
+                h1 class="text-4xl font-extrabold" {(book_subscription.name)}
+                @if chapters.is_empty() {
+                    p class="text-gray-500" { "No graded chapters yet" }
+                } @else {
+                    (chart("Total Points", &chapters, &series, user.id, |series| &series.points, false))
+                    (chart("Rank", &chapters, &series, user.id, |series| &series.ranks, true))
+                }
+            }
+        }),
+        None,
+    )
+}
+
+fn chart(
+    title: &str,
+    chapters: &[(i32, &str)],
+    series: &[Series],
+    highlight_user_id: i32,
+    values: impl for<'s> Fn(&'s Series<'_>) -> &'s [i32],
+    inverted: bool,
+) -> maud::Markup {
+    let (min, max) = series
+        .iter()
+        .flat_map(|series| values(series).iter().copied())
+        .minmax()
+        .into_option()
+        .unwrap_or((0, 0));
+    // Points charts always include zero so that totals are comparable at a glance
+    let min = if inverted { min } else { min.min(0) };
+    let range = (max - min).max(1) as f64;
+
+    let x = |i: usize| {
+        PADDING + i as f64 * (WIDTH - 2.0 * PADDING) / (chapters.len().max(2) - 1) as f64
+    };
+    let y = |value: i32| {
+        let ratio = (value - min) as f64 / range;
+        let ratio = if inverted { ratio } else { 1.0 - ratio };
+        PADDING + ratio * (HEIGHT - 2.0 * PADDING)
+    };
+
+    maud::html! {
+        figure class="w-full max-w-3xl p-3 bg-white border border-gray-200 rounded-lg shadow-sm" {
+            figcaption class="text-lg font-semibold text-center" { (title) }
+            svg viewBox={"0 0 "(WIDTH)" "(HEIGHT)} class="w-full h-auto overflow-visible" role="img" {
+                line x1=(PADDING) y1=(HEIGHT - PADDING) x2=(WIDTH - PADDING) y2=(HEIGHT - PADDING) stroke="#d1d5db" {}
+                line x1=(PADDING) y1=(PADDING) x2=(PADDING) y2=(HEIGHT - PADDING) stroke="#d1d5db" {}
+                text x=(PADDING - 6.0) y=(y(max) + 4.0) text-anchor="end" font-size="10" fill="#6b7280" { (max) }
+                text x=(PADDING - 6.0) y=(y(min) + 4.0) text-anchor="end" font-size="10" fill="#6b7280" { (min) }
+                @for (i, (_, chapter_title)) in chapters.iter().enumerate() {
+                    text x=(x(i)) y=(HEIGHT - PADDING + 14.0) text-anchor="middle" font-size="10" fill="#6b7280" { (chapter_title) }
+                }
+                @for series in series {
+                    @let highlighted = series.user_id == highlight_user_id && series.entry_id.is_none();
+                    @let points = values(series)
+                        .iter()
+                        .enumerate()
+                        .map(|(i, value)| format!("{:.1},{:.1}", x(i), y(*value)))
+                        .join(" ");
+                    g {
+                        title { (series.username) }
+                        polyline
+                            points=(points)
+                            fill="none"
+                            stroke=(if highlighted { "#2563eb" } else { "#9ca3af" })
+                            stroke-width=(if highlighted { "3" } else { "1.5" }) {}
+                        @if let Some(last) = values(series).last() {
+                            text
+                                x=(x(chapters.len() - 1) + 4.0)
+                                y=(y(*last) + 3.0)
+                                font-size="10"
+                                font-weight=(if highlighted { "bold" } else { "normal" })
+                                fill=(if highlighted { "#2563eb" } else { "#6b7280" }) {
+                                (series.username)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -17,7 +17,9 @@ pub fn markup(books: Vec<BookSubscriptionStats>) -> maud::Markup {
                                     h1 class="text-2xl font-bold" { (book.name) }
                                     p class="text-xs italic" { (book.num_members) " Members" }
                                 }
-                                @if book.num_members > 1 {
+                                @if book.is_spectator {
+                                    p class="text-xs text-gray-500" { "Spectating" }
+                                } @else if book.num_members > 1 {
                                     p {
                                         (book.rank)
                                         @if book.rank == 1 {
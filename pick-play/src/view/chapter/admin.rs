@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
 use crate::model::{
-    chapter::Chapter,
-    event::{Event, EventContent},
+    book::{BookCapability, BookRole},
+    chapter::{Chapter, ChapterUser},
+    event::{Event, EventContent, UserPick},
+    extension::DeadlineExtension,
+    grading::{EventChange, GradingChange},
     spread::Spread,
     user_input::UserInput,
 };
@@ -12,6 +15,7 @@ use super::super::authenticated;
 pub fn m(
     username: &str,
     book_name: &str,
+    role: &BookRole,
     chapter: Chapter,
     events: Vec<Event>,
     relevent_teams: HashMap<i32, (String, Option<String>)>,
@@ -34,10 +38,49 @@ pub fn m(
         }),
         Some(maud::html! {
             div class="flex flex-col items-center justify-center" {
-                div class="self-center justify-center p-2 m-3 bg-white border border-gray-300 rounded-lg shadow-md w-fit" {
-                    (chapter_open_button(chapter.is_open))
+                @if role.has_capability(BookCapability::ManageChapters) {
+                    div class="self-center justify-center p-2 m-3 bg-white border border-gray-300 rounded-lg shadow-md w-fit" {
+                        (chapter_open_button(chapter.is_open))
 
-                    (chapter_visible_button(chapter.is_visible))
+                        (chapter_visible_button(chapter.is_visible))
+
+                        div hx-get="order" hx-trigger="load" hx-swap="outerHTML" {}
+                    }
+
+                    div class="flex items-center justify-center" {
+                        details class="w-full max-w-xl" {
+                            summary class="p-3 my-1 align-middle bg-green-500 rounded-lg shadow-md select-none w-max" {
+                                "Deadline Extensions"
+                            }
+                            div hx-get="extensions" hx-trigger="load" hx-swap="outerHTML" class="mt-2" {
+                                "Loading..."
+                            }
+                        }
+                    }
+                }
+
+                @if role.has_capability(BookCapability::ManageMembers) {
+                    div class="flex items-center justify-center" {
+                        details class="w-full max-w-xl" {
+                            summary class="p-3 my-1 align-middle bg-green-500 rounded-lg shadow-md select-none w-max" {
+                                "Pick History"
+                            }
+                            div hx-get="revisions" hx-trigger="toggle once from:closest details" hx-swap="outerHTML" class="mt-2" {
+                                "Loading..."
+                            }
+                        }
+                    }
+
+                    div class="flex items-center justify-center" {
+                        details class="w-full max-w-xl" {
+                            summary class="p-3 my-1 align-middle bg-green-500 rounded-lg shadow-md select-none w-max" {
+                                "Enter Picks for a Member"
+                            }
+                            div hx-get="proxy" hx-trigger="toggle once from:closest details" hx-swap="outerHTML" class="mt-2" {
+                                "Loading..."
+                            }
+                        }
+                    }
                 }
 
                 div class="flex items-center justify-center" {
@@ -51,28 +94,43 @@ pub fn m(
                     }
                 }
 
-                form hx-post="." hx-ext="my-enc" hx-swap="afterend" {
-                    @for (i, event) in events.into_iter().enumerate() {
-                        fieldset name="events" me-insert="array" class="flex items-center justify-center" {
-                            input type="hidden" name="event-id" value=(event.id);
-                            @match event.contents.0 {
-                                EventContent::SpreadGroup(spreads) => {
-                                    input type="hidden" name="type" value="spread-group";
-                                    (spread_group(spreads, i, &relevent_teams))
-                                },
-                                EventContent::UserInput(input) => {
-                                    input type="hidden" name="type" value="user-input";
-                                    (user_input(input, event.id, i))
-                                }
+                @if role.has_capability(BookCapability::Grade) {
+                    div class="flex items-center justify-center" {
+                        details class="w-full max-w-3xl" {
+                            summary class="p-3 my-1 align-middle bg-green-500 rounded-lg shadow-md select-none w-max" {
+                                "Grading History"
+                            }
+                            div hx-get="history" hx-trigger="toggle once from:closest details" hx-swap="outerHTML" class="mt-2" {
+                                "Loading..."
                             }
                         }
                     }
 
-                    button type="submit" class="px-2 py-2 mt-1 mb-10 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
-                        "Submit"
+                    form hx-post="." hx-ext="my-enc" hx-swap="afterend" {
+                        @for (i, event) in events.into_iter().enumerate() {
+                            fieldset name="events" me-insert="array" class="flex items-center justify-center" {
+                                input type="hidden" name="event-id" value=(event.id);
+                                @match event.contents.0 {
+                                    EventContent::SpreadGroup(spreads) => {
+                                        input type="hidden" name="type" value="spread-group";
+                                        (spread_group(spreads, i, &relevent_teams))
+                                    },
+                                    EventContent::UserInput(input) => {
+                                        input type="hidden" name="type" value="user-input";
+                                        (user_input(input, event.id, i))
+                                    }
+                                }
+                            }
+                        }
+
+                        button type="submit" class="px-2 py-2 mt-1 mb-10 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                            "Submit"
+                        }
+                        div {}
                     }
-                    div {}
+                }
 
+                @if role.has_capability(BookCapability::ManageChapters) {
                     details class="my-5" {
                         summary {
                             span class="text-red-500" {"Danger Zone"}
@@ -146,6 +204,33 @@ pub fn chapter_visible_button(is_visible: bool) -> maud::Markup {
     }
 }
 
+pub fn chapter_order_buttons(place: i64, total: i64) -> maud::Markup {
+    maud::html! {
+        div class="flex items-center justify-between p-3 border-t border-gray-200" id="chapter-order" {
+            span class="text-sm font-medium" { "Chapter Order:" }
+            div class="flex items-center gap-2 ml-3" {
+                button
+                    hx-post="order?earlier=true"
+                    hx-target="#chapter-order"
+                    hx-swap="outerHTML"
+                    disabled[place <= 1]
+                    class="px-2 font-bold text-white bg-orange-600 rounded hover:bg-orange-700 disabled:opacity-50" {
+                    "↑"
+                }
+                span class="text-sm" { (place) " of " (total) }
+                button
+                    hx-post="order?earlier=false"
+                    hx-target="#chapter-order"
+                    hx-swap="outerHTML"
+                    disabled[place >= total]
+                    class="px-2 font-bold text-white bg-orange-600 rounded hover:bg-orange-700 disabled:opacity-50" {
+                    "↓"
+                }
+            }
+        }
+    }
+}
+
 fn spread_group(
     spreads: Vec<Spread>,
     index: usize,
@@ -186,9 +271,9 @@ fn spread_group(
                     }
                 }
                 div {
-                    input type="radio" name={"selections["(index)"-"(i)"]"} me-insert="array" class="absolute opacity-0 peer" value="unpicked" id={(index)"-"(i)"-unpicked"} checked[spread.answer.is_none() || spread.answer == Some("unpicked".into())];
-                    label for={(index)"-"(i)"-unpicked"} class="inline-grid w-10/12 p-5 pt-0 pb-0 mb-1 border border-black rounded-lg cursor-pointer hover:border-green-700 peer-checked:bg-green-500 peer-checked:border-green-600 hover:bg-green-100" {
-                        p class="px-1 font-semibold" { "Unpicked" }
+                    input type="radio" name={"selections["(index)"-"(i)"]"} me-insert="array" class="absolute opacity-0 peer" value="pending" id={(index)"-"(i)"-pending"} checked[spread.answer.is_none() || spread.answer == Some("unpicked".into())];
+                    label for={(index)"-"(i)"-pending"} class="inline-grid w-10/12 p-5 pt-0 pb-0 mb-1 border border-black rounded-lg cursor-pointer hover:border-green-700 peer-checked:bg-green-500 peer-checked:border-green-600 hover:bg-green-100" {
+                        p class="px-1 font-semibold" { "Pending" }
                     }
                 }
             }
@@ -213,3 +298,190 @@ fn user_input(input: UserInput, event_id: i32, _index: usize) -> maud::Markup {
         }
     }
 }
+
+fn answers(content: &EventContent, teams: &HashMap<i32, (String, Option<String>)>) -> Vec<String> {
+    let team = |id: &i32| teams.get(id).map_or("Unknown", |(name, _)| name.as_str());
+    match content {
+        EventContent::SpreadGroup(spreads) => spreads
+            .iter()
+            .map(|spread| match spread.answer.as_deref() {
+                Some("home") => team(&spread.home_id).to_string(),
+                Some("away") => team(&spread.away_id).to_string(),
+                Some("push") => "Push".to_string(),
+                _ => "Pending".to_string(),
+            })
+            .collect(),
+        EventContent::UserInput(input) => vec![input
+            .acceptable_answers
+            .as_ref()
+            .map_or("Pending".to_string(), |answers| {
+                answers.iter().cloned().collect::<Vec<_>>().join(", ")
+            })],
+    }
+}
+
+fn event_change(
+    change: &EventChange,
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> maud::Markup {
+    let team = |id: &i32| teams.get(id).map_or("Unknown", |(name, _)| name.as_str());
+    let titles = match &change.after {
+        EventContent::SpreadGroup(spreads) => spreads
+            .iter()
+            .map(|spread| format!("{} @ {}", team(&spread.away_id), team(&spread.home_id)))
+            .collect(),
+        EventContent::UserInput(input) => vec![input.title.clone()],
+    };
+    let before = answers(&change.before, teams);
+    let after = answers(&change.after, teams);
+
+    maud::html! {
+        @for ((title, before), after) in titles.iter().zip(&before).zip(&after) {
+            @if before != after {
+                li {
+                    (title) ": "
+                    span class="text-red-600 line-through" { (before) }
+                    " → "
+                    span class="font-semibold text-green-700" { (after) }
+                }
+            }
+        }
+    }
+}
+
+pub fn grading_history(
+    history: &[GradingChange],
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> maud::Markup {
+    maud::html! {
+        div class="flex flex-col gap-2" {
+            @if history.is_empty() {
+                p class="text-gray-500" { "This chapter has not been graded yet" }
+            }
+            @for change in history {
+                div class="p-3 text-sm bg-white border border-gray-300 rounded-lg shadow-sm" {
+                    div class="flex items-center justify-between gap-2" {
+                        p {
+                            span class="font-semibold" { (change.username) }
+                            span class="text-gray-500" { " on " (change.created_at) }
+                            @if let Some(reverted_change_id) = change.reverted_change_id {
+                                span class="text-orange-600" { " (reverted change #" (reverted_change_id) ")" }
+                            }
+                        }
+                        button
+                            hx-post={"history/"(change.id)"/revert"}
+                            hx-confirm="Revert this chapter's answers to how they were before this change?"
+                            class="px-2 py-1 text-xs font-bold text-white bg-orange-600 rounded hover:bg-orange-700" {
+                            "Undo this and later changes"
+                        }
+                    }
+                    ul class="mt-1 ml-4 list-disc" {
+                        @for event in &change.events.0 {
+                            (event_change(event, teams))
+                        }
+                    }
+                    @if !change.point_deltas.0.is_empty() {
+                        p class="mt-1 text-gray-600" {
+                            @for (i, delta) in change.point_deltas.0.iter().enumerate() {
+                                @if i > 0 { ", " }
+                                (delta.username) " "
+                                span.text-green-600[delta.change() > 0].text-red-600[delta.change() < 0] {
+                                    (format!("{:+}", delta.change()))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Lets an admin enter picks for a member, swapped in place when another
+/// member is picked
+pub fn proxy_picker(
+    is_open: bool,
+    users: &[ChapterUser],
+    selected: Option<i32>,
+    picks: Option<Vec<UserPick>>,
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> maud::Markup {
+    maud::html! {
+        div class="flex flex-col items-center gap-2" {
+            @if is_open {
+                form hx-get="proxy" hx-trigger="change" hx-target="closest div" hx-swap="outerHTML" {
+                    select name="user_id" class="border border-green-300 rounded" {
+                        option value="" selected[selected.is_none()] { "Choose a member" }
+                        @for user in users {
+                            option value=(user.user_id) selected[selected == Some(user.user_id)] {
+                                (user.username)
+                                @if let Some(entered_by) = &user.entered_by {
+                                    " (entered by " (entered_by) ")"
+                                }
+                            }
+                        }
+                    }
+                }
+                @if let (Some(user_id), Some(picks)) = (selected, picks) {
+                    (crate::view::chapter::open::pick_form(picks, teams, &format!("proxy/{user_id}"), false))
+                }
+            } @else {
+                p class="text-gray-500" { "Picks can only be entered while the chapter is open" }
+            }
+        }
+    }
+}
+
+/// Members allowed to submit after the chapter locks, swapped in place as
+/// extensions are granted and revoked
+pub fn extensions(users: &[ChapterUser], extensions: &[DeadlineExtension]) -> maud::Markup {
+    maud::html! {
+        div id="extensions" class="flex flex-col items-center gap-2" {
+            form hx-post="extensions" hx-target="#extensions" hx-swap="outerHTML" class="flex flex-wrap items-center justify-center gap-2" {
+                select name="user_id" required class="border border-green-300 rounded" {
+                    option value="" { "Choose a member" }
+                    @for user in users {
+                        option value=(user.user_id) { (user.username) }
+                    }
+                }
+                input type="number" name="hours" min="1" value="24" required class="w-16 border border-green-300";
+                span { "hours from now" }
+                button type="submit" class="px-2 py-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                    "Extend"
+                }
+            }
+            table class="w-full text-sm text-left text-gray-500" {
+                thead class="text-xs text-gray-700 uppercase bg-gray-100" {
+                    tr {
+                        th scope="col" class="px-3 py-2" { "member" }
+                        th scope="col" class="px-3 py-2" { "until" }
+                        th scope="col" class="px-3 py-2" { "granted by" }
+                        th scope="col" class="px-3 py-2" { "action" }
+                    }
+                }
+                tbody {
+                    @if extensions.is_empty() {
+                        tr { td colspan="4" class="px-3 py-2 text-gray-500" { "No extensions" } }
+                    }
+                    @for extension in extensions {
+                        tr.line-through[!extension.is_active] class="bg-white" {
+                            td class="px-3 py-2 font-medium text-gray-900" { (extension.username) }
+                            td class="px-3 py-2" { (extension.expires_at) }
+                            td class="px-3 py-2" { (extension.granted_by) }
+                            td class="px-3 py-2" {
+                                button
+                                    hx-delete="extensions"
+                                    hx-vals={r#"{"user_id":""#(extension.user_id)r#""}"#}
+                                    hx-target="#extensions"
+                                    hx-swap="outerHTML"
+                                    class="px-2 py-1 font-bold text-white bg-orange-600 rounded hover:bg-orange-700" {
+                                    "Revoke"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -3,8 +3,10 @@ use std::collections::HashMap;
 use crate::{
     controllers::auth::BackendUser,
     model::{
-        book::{BookRole, BookSubscription},
+        book::BookSubscription,
         chapter::{Chapter, ChapterUser},
+        commitment::ChapterCommitment,
+        consensus::{Consensus, ConsensusRecord},
         event::{ChapterPick, ChapterPickHash, Event, EventContent},
     },
 };
@@ -185,13 +187,19 @@ pub fn m(
         }),
         Some(maud::html! {
             div class="flex flex-col flex-grow min-h-screen bg-gray-50" {
-                @if book_subscription.role == BookRole::Admin {
+                @if book_subscription.role.has_any_capability() {
                     a href="admin/" {
                         button class="fixed z-50 px-3 py-2 text-sm font-bold text-white transition-colors bg-orange-600 rounded-full shadow-lg bottom-4 right-4 hover:bg-orange-700" {
                             "Admin"
                         }
                     }
                 }
+                p class="mb-2 text-sm text-center" {
+                    a href="what-if" class="text-blue-400 hover:underline" { "What If" }
+                    " · "
+                    a href="revisions" class="text-blue-400 hover:underline" { "My Pick History" }
+                }
+                div hx-get="commitment" hx-trigger="load" hx-swap="outerHTML" {}
                 div class="mx-4 mb-3 mobile-toggle-container" {
                     div class="max-w-sm mx-auto toggle-pill" {
                         div class="toggle-slider" {}
@@ -222,6 +230,7 @@ pub fn m(
 
                 div id="events-section" class="mx-4 section-content" {
                     h2 class="hidden mb-4 text-xl font-bold text-gray-900 md:block" { "Event Results" }
+                    div hx-get="../../consensus" hx-trigger="load" hx-swap="outerHTML" {}
                     (event_tiles(events, users, user_picks, relevent_teams))
                 }
 
@@ -256,6 +265,7 @@ fn user_points(
         let user_pick = user_picks.get(&ChapterPickHash {
             event_id: event.id,
             user_id: user.user_id,
+            entry_id: user.entry_id,
         });
         match (&event.contents.0, &user_pick) {
             (EventContent::SpreadGroup(spreads), Some(ChapterPick::SpreadGroup { choice, .. })) => {
@@ -319,6 +329,8 @@ fn leaderboard(
                                         td class="px-3 py-2" {
                                             div class="flex items-center gap-2" {
                                                 span class="font-medium text-gray-900" { (user.username) }
+                                                (proxy_indicator(user))
+                                                (missed_indicator(user))
                                             }
                                         }
                                         @let correct_questions = user_points(user, events, user_picks);
@@ -383,6 +395,9 @@ fn user_input_tile(
                         }
                     }
                     div class="flex-shrink-0 text-right" {
+                        @if input.acceptable_answers.is_none() {
+                            span class="block px-1.5 py-0.5 mb-1 text-xs font-medium text-gray-700 bg-gray-200 rounded-full" { "Pending" }
+                        }
                         span class="text-xl font-bold text-blue-600" { (input.points) }
                         p class="text-sm text-gray-500" { "Point" @if input.points > 1 {"s"} }
                     }
@@ -392,7 +407,7 @@ fn user_input_tile(
                 div class="space-y-2" {
                     div class="space-y-2 overflow-y-auto max-h-48 overscroll-contain" {
                         @for user in users {
-                            @let user_pick = user_picks.get(&ChapterPickHash{event_id: event.id, user_id: user.user_id});
+                            @let user_pick = user_picks.get(&ChapterPickHash{event_id: event.id, user_id: user.user_id, entry_id: user.entry_id});
                             @match user_pick {
                                 Some(ChapterPick::UserInput{choice, wager: _wager, points}) => {
                                     @let (bg_color, icon) = match points {
@@ -441,14 +456,17 @@ fn spread_tile(
 ) -> maud::Markup {
     let mut points_wagered = 0;
     let mut points_awarded = 0;
+    let mut consensus = Consensus::default();
     for user in users {
         if let Some(ChapterPick::SpreadGroup { choice, wager, .. }) =
             user_picks.get(&ChapterPickHash {
                 event_id: event.id,
                 user_id: user.user_id,
+                entry_id: user.entry_id,
             })
         {
             points_wagered += wager[index];
+            consensus.add(&choice[index], wager[index]);
             match &event.contents.0 {
                 EventContent::SpreadGroup(spreads)
                     if spreads[index]
@@ -483,25 +501,31 @@ fn spread_tile(
                 div class="flex items-center justify-between mb-3" {
                     div class="text-left" {
                         h3.text-red-500[is_answered] class="text-base font-semibold" {
-                            span.text-green-500[team_win("away")] { (relevent_teams[&spread.away_id].0) }
+                            a.text-green-500[team_win("away")] href={"/book/"(event.book_id)"/team/"(spread.away_id)} class="hover:underline" { (relevent_teams[&spread.away_id].0) }
                             span class="text-sm font-normal text-gray-500" { (format!(" ({:+})", -1. * spread.home_spread)) }
                             span class="ml-2 text-sm font-normal text-gray-500" { "at" }
                             br;
-                            span.text-green-500[team_win("home")]{ (relevent_teams[&spread.home_id].0) }
+                            a.text-green-500[team_win("home")] href={"/book/"(event.book_id)"/team/"(spread.home_id)} class="hover:underline" { (relevent_teams[&spread.home_id].0) }
                             span class="text-sm font-normal text-gray-500" { (format!(" ({:+})", spread.home_spread)) }
                         }
                     }
                     div class="text-right" {
+                        @if !is_answered {
+                            span class="px-1.5 py-0.5 text-xs font-medium text-gray-700 bg-gray-200 rounded-full" { "Pending" }
+                        }
                         p class="text-sm text-gray-600" { "Wagered: " (points_wagered) }
                         p class="text-sm text-gray-600" { "Awarded: " (points_awarded) }
                     }
                 }
+                @if consensus.total() > 0 {
+                    (consensus_bar(&consensus, &relevent_teams[&spread.away_id].0, &relevent_teams[&spread.home_id].0))
+                }
             }
             div class="p-4 pt-0" {
                 div class="space-y-2" {
                     div class="space-y-2 overflow-y-auto max-h-48 overscroll-contain" {
                         @for user in users {
-                            @let user_pick = user_picks.get(&ChapterPickHash{event_id: event.id, user_id: user.user_id});
+                            @let user_pick = user_picks.get(&ChapterPickHash{event_id: event.id, user_id: user.user_id, entry_id: user.entry_id});
                             @match user_pick {
                                 Some(ChapterPick::SpreadGroup{choice, wager, ..}) => {
                                     @let is_correct = spread.answer.as_ref().map(|a| *a == choice[index]).unwrap_or(false);
@@ -522,6 +546,9 @@ fn spread_tile(
                                     div class={(format!("flex items-center justify-between p-2 rounded-md border {}", bg_color))} {
                                         div class="flex items-center gap-2" {
                                             span class="font-medium text-gray-900" { (user.username) }
+                                            @if consensus.is_contrarian(&choice[index]) {
+                                                span class="px-1.5 py-0.5 text-xs font-medium text-purple-700 bg-purple-100 rounded-full" title="Went against the majority" { "Contrarian" }
+                                            }
                                         }
                                         div class="text-right" {
                                             div class="flex items-center gap-1" {
@@ -555,6 +582,103 @@ fn spread_tile(
     )
 }
 
+fn consensus_bar(consensus: &Consensus, away_team: &str, home_team: &str) -> maud::Markup {
+    let confidence = |confidence: Option<f64>| {
+        confidence.map_or("-".to_string(), |confidence| format!("{confidence:.1}"))
+    };
+
+    maud::html!(
+        div class="mb-2" {
+            div class="flex justify-between text-xs text-gray-600" {
+                span { (away_team) " " (format!("{:.0}%", consensus.away_percent())) }
+                span { (format!("{:.0}%", consensus.home_percent())) " " (home_team) }
+            }
+            div class="flex w-full h-2 overflow-hidden bg-gray-200 rounded-full" {
+                div class="bg-blue-500" style=(format!("width: {:.1}%", consensus.away_percent())) {}
+                div class="bg-orange-500" style=(format!("width: {:.1}%", consensus.home_percent())) {}
+            }
+            div class="flex justify-between text-xs text-gray-500" {
+                span { "Avg wager: " (confidence(consensus.away_confidence())) }
+                span { "Avg wager: " (confidence(consensus.home_confidence())) }
+            }
+        }
+    )
+}
+
+pub fn season_consensus(record: &ConsensusRecord) -> maud::Markup {
+    let decided = record.wins + record.losses;
+
+    maud::html!(
+        p class="mb-4 text-sm text-gray-600" {
+            "Book consensus this season: "
+            span class="font-semibold text-gray-900" {
+                (record.wins) "-" (record.losses)
+                @if record.pushes > 0 {
+                    "-" (record.pushes)
+                }
+            }
+            @if decided > 0 {
+                " (" (format!("{:.0}%", record.wins as f64 / decided as f64 * 100.0)) ")"
+            }
+        }
+    )
+}
+
+/// Every root published for the chapter, so a relock or late submission
+/// can't quietly replace an earlier one
+pub fn commitment(commitments: &[ChapterCommitment], verified: Option<bool>) -> maud::Markup {
+    maud::html!(
+        div class="mb-3 text-xs text-center text-gray-500" {
+            @if commitments.is_empty() {
+                p { "No commitment was recorded when this chapter locked" }
+            }
+            @for commitment in commitments {
+                p {
+                    "Picks committed " (commitment.committed_at)
+                    @if let Some(late_username) = &commitment.late_username {
+                        " after a late submission by " (late_username)
+                    }
+                    ": "
+                    code class="break-all" title="SHA-256 root over every member's picks" { (commitment.root) }
+                }
+            }
+            @if commitments.len() > 1 {
+                p class="font-semibold text-amber-600" { "Picks were committed more than once for this chapter" }
+            }
+            @match verified {
+                Some(true) => p class="text-green-600" {
+                    "Your picks match the latest commitment · "
+                    a href="receipt" class="text-blue-400 hover:underline" { "Download receipt" }
+                },
+                Some(false) => p class="font-semibold text-red-600" { "Your picks have changed since they were last committed" },
+                None => {},
+            }
+        }
+    )
+}
+
+/// Marks picks an admin entered for the user
+fn proxy_indicator(user: &ChapterUser) -> maud::Markup {
+    maud::html!(
+        @if let (Some(entered_by), Some(entered_at)) = (&user.entered_by, &user.entered_at) {
+            span class="text-xs text-gray-500 cursor-help" title=(format!("Picks entered by {entered_by} at {entered_at}")) {
+                "📞 by " (entered_by)
+            }
+        }
+    )
+}
+
+/// Marks users whose missed picks were filled in by the book's policy
+fn missed_indicator(user: &ChapterUser) -> maud::Markup {
+    maud::html!(
+        @if let Some(policy) = user.missed_policy {
+            span class="px-1.5 py-0.5 text-xs text-purple-700 bg-purple-100 rounded-full cursor-help" title=(format!("Missed picks: {}", policy.description())) {
+                "🤖 " (policy.label())
+            }
+        }
+    )
+}
+
 fn table_header(
     events: &[Event],
     relevent_teams: &HashMap<i32, (String, Option<String>)>,
@@ -569,10 +693,10 @@ fn table_header(
                             @for spread in group {
                                 th class="px-3 py-3 text-sm font-medium text-center text-gray-700 border-b border-gray-200 min-w-32" {
                                     div class="space-y-1" {
-                                        p class="text-xs" { (relevent_teams[&spread.away_id].0) }
+                                        a href={"/book/"(event.book_id)"/team/"(spread.away_id)} class="block text-xs hover:underline" { (relevent_teams[&spread.away_id].0) }
                                         p class="text-xs text-gray-500" { (format!("({:+})", -1. * spread.home_spread)) }
                                         p class="text-xs" { "at" }
-                                        p class="text-xs" { (relevent_teams[&spread.home_id].0) }
+                                        a href={"/book/"(event.book_id)"/team/"(spread.home_id)} class="block text-xs hover:underline" { (relevent_teams[&spread.home_id].0) }
                                     }
                                 }
                             }
@@ -598,7 +722,7 @@ fn table_rows(
     maud::html!(
         tbody class="divide-y divide-gray-200" {
             // Each user
-            @for ChapterUser { user_id, username, total_points, rank: _rank } in users {
+            @for ChapterUser { user_id, entry_id, username, total_points, .. } in users {
                 tr class="hover:bg-gray-50" {
                     td class="px-4 py-3 bg-gray-200 border-b border-gray-200 opacity-100" {
                         p class="font-medium text-gray-900" {(username)}
@@ -607,7 +731,7 @@ fn table_rows(
                     // Each event
                     @for event in events {
                         // Event type
-                        @match (&event.contents.0, picks_by_user.get(&ChapterPickHash{event_id: event.id, user_id: *user_id})) {
+                        @match (&event.contents.0, picks_by_user.get(&ChapterPickHash{event_id: event.id, user_id: *user_id, entry_id: *entry_id})) {
                             (EventContent::SpreadGroup(spreads), Some(ChapterPick::SpreadGroup { choice, wager, .. })) => {
                                 @for (i, spread) in spreads.iter().enumerate() {
                                     @let bg_color = match spread.answer.as_ref().map(|a| *a == choice[i]) {
@@ -3,3 +3,6 @@ pub mod closed;
 pub mod create;
 pub mod list;
 pub mod open;
+pub mod projection;
+pub mod recap;
+pub mod revisions;
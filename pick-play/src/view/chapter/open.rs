@@ -5,6 +5,7 @@ use maud::{html, Markup};
 
 use crate::model::{
     chapter::Chapter,
+    entry::{BookEntry, OwnedEntries},
     event::{EventContent, Pick, UserPick},
     spread::Spread,
     user_input::UserInput,
@@ -19,6 +20,7 @@ pub fn m(
     user_picks: Vec<UserPick>,
     is_admin: bool,
     relevent_teams: HashMap<i32, (String, Option<String>)>,
+    entries: &OwnedEntries,
 ) -> Markup {
     authenticated(
         username,
@@ -43,29 +45,189 @@ pub fn m(
                     }
                 }
             }
-            form id="submit-picks" hx-post="." hx-ext="my-enc" {
-                @if user_picks.is_empty() {
-                    p { "No Events in this Chapter" }
-                }
-                @for (i, (event, pick)) in user_picks.into_iter().enumerate() {
-                    fieldset name="events" me-insert="array" class="flex items-center justify-center" {
-                        input type="hidden" name="event-id" value=(event.id);
-                        @match event.contents.0 {
-                            EventContent::SpreadGroup(spreads) => (spread_group(spreads, pick, i, &relevent_teams)),
-                            EventContent::UserInput(input) => (user_input(input, pick))
+            p class="mb-2 text-sm text-center" {
+                a href="revisions" class="text-blue-400 hover:underline" { "My Pick History" }
+            }
+            (entry_switcher(username, chapter, entries, ""))
+            div hx-get="draft" hx-trigger="load, htmx:afterRequest from:#submit-picks" {}
+            (pick_form(user_picks, &relevent_teams, ".", true))
+            div {}
+        }),
+        None,
+    )
+}
+
+/// Chooses which entry is being edited, and opens new ones when the book
+/// allows it. `selected` is the page's path within the chapter, empty for
+/// the user's own entry.
+fn entry_switcher(
+    username: &str,
+    chapter: &Chapter,
+    entries: &OwnedEntries,
+    selected: &str,
+) -> Markup {
+    let chapter_url = format!("/book/{}/chapter/{}/", chapter.book_id, chapter.chapter_id);
+    let has_others = !entries.managed.is_empty() || !entries.entries.is_empty();
+
+    html! {
+        @if has_others || entries.can_add {
+            div class="flex flex-wrap items-center justify-center gap-2 mb-2 text-sm" {
+                @if has_others {
+                    label {
+                        "Editing "
+                        select class="border border-green-300 rounded" onchange="window.location = this.value" {
+                            option value=(chapter_url) selected[selected.is_empty()] { (username) }
+                            @for entry in &entries.entries {
+                                @let path = format!("entry/{}/", entry.id);
+                                option value={(chapter_url)(path)} selected[selected == path] { (username) " (" (entry.name) ")" }
+                            }
+                            @for (user_id, managed_username) in &entries.managed {
+                                @let path = format!("for/{user_id}");
+                                option value={(chapter_url)(path)} selected[selected == path] { (managed_username) }
+                            }
                         }
                     }
                 }
-                button type="submit" class="px-2 py-2 mt-1 mb-10 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
-                    "Submit"
+                @if entries.can_add {
+                    form hx-post={"/book/"(chapter.book_id)"/entries"} class="flex items-center gap-1" {
+                        input type="text" name="name" maxlength="20" placeholder="Entry name" required class="border border-green-300 rounded";
+                        button type="submit" class="px-2 py-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" { "New Entry" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether a complete set of picks is on file, and when the draft was saved
+pub fn pick_status(submitted: bool, draft_saved_at: Option<&str>) -> Markup {
+    html! {
+        div class="mb-2 text-sm text-center" {
+            @if submitted {
+                p class="font-semibold text-green-700" { "✓ Your picks are submitted" }
+            } @else {
+                p class="font-semibold text-red-600" { "No complete set of picks on file yet" }
+            }
+            @if let Some(saved_at) = draft_saved_at {
+                p class="text-gray-500" {
+                    "Draft saved " (saved_at) ". "
+                    @if submitted {
+                        "Submit to replace the picks on file."
+                    } @else {
+                        "Submit once every pick is filled out."
+                    }
                 }
             }
+        }
+    }
+}
+
+/// Picks for a managed member, made by the member that owns them
+pub fn managed(
+    username: &str,
+    book_name: &str,
+    chapter: &Chapter,
+    (managed_id, managed_username): (i32, &str),
+    user_picks: Vec<UserPick>,
+    relevent_teams: &HashMap<i32, (String, Option<String>)>,
+    entries: &OwnedEntries,
+) -> Markup {
+    authenticated(
+        username,
+        Some(&format!("{} - {managed_username}", chapter.title)),
+        None,
+        Some(html! {
+            script src="/public/js/my-enc.js" {}
+            (crate::view::alertify())
+        }),
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a href="../../.." class="text-blue-400 hover:underline" { (book_name) } " > "
+                a href=".." class="text-blue-400 hover:underline" { (chapter.title) } " > "
+                a { (managed_username) }
+            }
+        }),
+        Some(html! {
+            (entry_switcher(username, chapter, entries, &format!("for/{managed_id}")))
+            h1 class="mb-2 text-2xl font-bold" { "Picks for " (managed_username) }
+            (pick_form(user_picks, relevent_teams, &managed_id.to_string(), false))
             div {}
         }),
         None,
     )
 }
 
+/// Picks for one of the user's extra entries, drafted like their own
+pub fn entry(
+    username: &str,
+    book_name: &str,
+    chapter: &Chapter,
+    entry: &BookEntry,
+    user_picks: Vec<UserPick>,
+    relevent_teams: &HashMap<i32, (String, Option<String>)>,
+    entries: &OwnedEntries,
+) -> Markup {
+    authenticated(
+        username,
+        Some(&format!("{} - {}", chapter.title, entry.name)),
+        None,
+        Some(html! {
+            script src="/public/js/my-enc.js" {}
+            (crate::view::alertify())
+        }),
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a href="../../../.." class="text-blue-400 hover:underline" { (book_name) } " > "
+                a href="../.." class="text-blue-400 hover:underline" { (chapter.title) } " > "
+                a { (entry.name) }
+            }
+        }),
+        Some(html! {
+            (entry_switcher(username, chapter, entries, &format!("entry/{}/", entry.id)))
+            h1 class="mb-2 text-2xl font-bold" { "Picks for " (entry.name) }
+            div hx-get="draft" hx-trigger="load, htmx:afterRequest from:#submit-picks" {}
+            (pick_form(user_picks, relevent_teams, ".", true))
+            div {}
+        }),
+        None,
+    )
+}
+
+/// The pick form, posting to `action`. Admins reuse it to enter picks for
+/// members. With `autosave` every change is kept as a draft.
+pub fn pick_form(
+    user_picks: Vec<UserPick>,
+    relevent_teams: &HashMap<i32, (String, Option<String>)>,
+    action: &str,
+    autosave: bool,
+) -> Markup {
+    html! {
+        form
+            id="submit-picks"
+            hx-post=(action)
+            hx-ext="my-enc"
+            onchange=[autosave.then_some("htmx.ajax('POST', 'draft', {source: this, swap: 'none'})")] {
+            @if user_picks.is_empty() {
+                p { "No Events in this Chapter" }
+            }
+            @for (i, (event, pick)) in user_picks.into_iter().enumerate() {
+                fieldset name="events" me-insert="array" class="flex items-center justify-center" {
+                    input type="hidden" name="event-id" value=(event.id);
+                    @match event.contents.0 {
+                        EventContent::SpreadGroup(spreads) => (spread_group(spreads, pick, i, relevent_teams)),
+                        EventContent::UserInput(input) => (user_input(input, pick))
+                    }
+                }
+            }
+            button type="submit" class="px-2 py-2 mt-1 mb-10 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                "Submit"
+            }
+        }
+    }
+}
+
 fn spread_group(
     spreads: Vec<Spread>,
     picks: Option<Pick>,
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use crate::model::{
+    book::BookSubscription,
+    chapter::Chapter,
+    projection::{Projection, RemainingGame},
+};
+
+pub fn m(
+    username: &str,
+    book_subscription: &BookSubscription,
+    chapter: &Chapter,
+    games: &[RemainingGame],
+    teams: &HashMap<i32, (String, Option<String>)>,
+    projections: &[Projection],
+) -> maud::Markup {
+    let team = |id: &i32| teams.get(id).map_or("Unknown", |(name, _)| name.as_str());
+
+    crate::view::authenticated(
+        username,
+        Some(format!("{} - {} What If", book_subscription.name, chapter.title).as_str()),
+        None,
+        None,
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a href="../.." class="text-blue-400 hover:underline" { (book_subscription.name) } " > "
+                a href="." class="text-blue-400 hover:underline" { (chapter.title) } " > "
+                a {"What If"}
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center gap-4" {
+                h1 class="text-4xl font-extrabold" { (chapter.title) " What If" }
+                @if games.is_empty() {
+                    p class="text-gray-500" { "Every game in this chapter has been graded" }
+                } @else {
+                    form
+                        hx-get="what-if/standings"
+                        hx-trigger="change"
+                        hx-target="#projection"
+                        hx-swap="outerHTML"
+                        class="flex flex-col w-full max-w-xl gap-2" {
+                        @for game in games {
+                            fieldset class="flex flex-wrap items-center justify-between gap-2 p-2 bg-white border border-gray-200 rounded-lg" {
+                                legend class="sr-only" { (team(&game.away_id)) " @ " (team(&game.home_id)) }
+                                @for (value, label) in [
+                                    ("away", team(&game.away_id).to_string()),
+                                    ("push", "Push".to_string()),
+                                    ("home", format!("{} ({:+})", team(&game.home_id), game.home_spread)),
+                                    ("", "Undecided".to_string()),
+                                ] {
+                                    label class="flex items-center gap-1 text-sm" {
+                                        input type="radio" name=(game.key()) value=(value) checked[value.is_empty()];
+                                        (label)
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                (standings(projections))
+            }
+        }),
+        None,
+    )
+}
+
+pub fn standings(projections: &[Projection]) -> maud::Markup {
+    let mut projections = projections.iter().collect::<Vec<_>>();
+    projections.sort_by_key(|projection| std::cmp::Reverse(projection.chapter_points));
+
+    maud::html! {
+        table id="projection" class="w-full max-w-3xl text-sm text-left text-gray-500" {
+            thead class="text-xs text-gray-700 uppercase bg-gray-100" {
+                tr {
+                    th scope="col" class="px-3 py-2" { "Member" }
+                    th scope="col" class="px-3 py-2" { "Chapter" }
+                    th scope="col" class="px-3 py-2" { "Max" }
+                    th scope="col" class="px-3 py-2" { "Season" }
+                    th scope="col" class="px-3 py-2" { "Max" }
+                }
+            }
+            tbody {
+                @for projection in projections {
+                    tr class="bg-white" {
+                        td class="px-3 py-2" { (projection.username) }
+                        td class="px-3 py-2" {
+                            (projection.chapter_points)
+                            (status(projection.chapter_clinched, projection.chapter_eliminated))
+                        }
+                        td class="px-3 py-2" { (projection.max_chapter_points) }
+                        td class="px-3 py-2" {
+                            (projection.season_points)
+                            (status(projection.season_clinched, projection.season_eliminated))
+                        }
+                        td class="px-3 py-2" { (projection.max_season_points) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn status(clinched: bool, eliminated: bool) -> maud::Markup {
+    maud::html! {
+        @if clinched {
+            span class="px-1 ml-1 text-xs font-bold text-white bg-green-600 rounded" { "Clinched" }
+        } @else if eliminated {
+            span class="px-1 ml-1 text-xs font-bold text-white bg-red-600 rounded" { "Eliminated" }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::model::{
+    book::BookSubscription,
+    chapter::Chapter,
+    recap::{Recap, RecapUser},
+};
+
+pub fn m(
+    username: &str,
+    book_subscription: &BookSubscription,
+    chapter: &Chapter,
+    recap: Option<&Recap>,
+) -> maud::Markup {
+    crate::view::authenticated(
+        username,
+        Some(format!("{} - {} Recap", book_subscription.name, chapter.title).as_str()),
+        None,
+        None,
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a href="../.." class="text-blue-400 hover:underline" { (book_subscription.name) } " > "
+                a href="." class="text-blue-400 hover:underline" { (chapter.title) } " > "
+                a {"Recap"}
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center gap-4" {
+                h1 class="text-4xl font-extrabold" { (chapter.title) " Recap" }
+                @if let Some(recap) = recap {
+                    div class="grid w-full max-w-3xl grid-cols-1 gap-4 md:grid-cols-2" {
+                        (card("Weekly Winner", maud::html! {
+                            @if recap.winners.is_empty() {
+                                p class="text-gray-500" { "Nobody scored this chapter" }
+                            } @else {
+                                p class="text-lg font-bold" { (users(&recap.winners)) }
+                                p class="text-sm text-gray-500" { (recap.winning_points) " points" }
+                            }
+                        }))
+                        (card("Biggest Upset", maud::html! {
+                            @if let Some(upset) = &recap.biggest_upset {
+                                p class="font-bold" { (upset.matchup) }
+                                p class="text-sm text-gray-500" {
+                                    (upset.covered) " covered, " (upset.wrong) " of " (upset.total) " picked against them"
+                                }
+                            } @else {
+                                p class="text-gray-500" { "No upsets" }
+                            }
+                        }))
+                        (card("Boldest Correct Pick", maud::html! {
+                            @if let Some(pick) = &recap.boldest_pick {
+                                p class="font-bold" { (user(&pick.user)) " took " (pick.team) }
+                                p class="text-sm text-gray-500" {
+                                    (pick.matchup) " for " (pick.wager) " points, picked by " (format!("{:.0}%", pick.percent)) " of the book"
+                                }
+                            } @else {
+                                p class="text-gray-500" { "Nobody hit against the crowd" }
+                            }
+                        }))
+                        (card("Biggest Movers", maud::html! {
+                            @if recap.movers.is_empty() {
+                                p class="text-gray-500" { "The standings held" }
+                            }
+                            ul {
+                                @for mover in &recap.movers {
+                                    li {
+                                        (user(&mover.user)) " "
+                                        span.text-green-600[mover.change() > 0].text-red-600[mover.change() < 0] class="font-bold" {
+                                            @if mover.change() > 0 { "▲" } @else { "▼" }
+                                            (mover.change().abs())
+                                        }
+                                        span class="text-sm text-gray-500" { " now #" (mover.rank) }
+                                    }
+                                }
+                            }
+                        }))
+                        (card("Perfect Cards", maud::html! {
+                            @if recap.perfect_cards.is_empty() {
+                                p class="text-gray-500" { "Nobody was perfect" }
+                            } @else {
+                                p class="font-bold" { (users(&recap.perfect_cards)) }
+                            }
+                        }))
+                    }
+                } @else {
+                    p class="text-gray-500" { "This chapter has not been graded yet" }
+                }
+            }
+        }),
+        None,
+    )
+}
+
+fn card(title: &str, body: maud::Markup) -> maud::Markup {
+    maud::html! {
+        div class="flex flex-col gap-1 p-4 bg-white border border-gray-200 rounded-lg shadow-sm" {
+            h2 class="text-xs text-gray-500 uppercase" { (title) }
+            (body)
+        }
+    }
+}
+
+fn user(user: &RecapUser) -> maud::Markup {
+    maud::html! {
+        a href={"/user/"(user.user_id)} class="text-blue-400 hover:underline" { (user.username) }
+    }
+}
+
+fn users(users: &[RecapUser]) -> maud::Markup {
+    maud::html! {
+        @for (i, recap_user) in users.iter().enumerate() {
+            @if i > 0 { ", " }
+            (user(recap_user))
+        }
+    }
+}
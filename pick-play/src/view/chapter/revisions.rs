@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::model::{
+    book::BookSubscription,
+    chapter::Chapter,
+    event::{Event, EventContent},
+    pick_revision::PickRevision,
+};
+
+pub fn m(
+    username: &str,
+    book_subscription: &BookSubscription,
+    chapter: &Chapter,
+    revisions: &[PickRevision],
+    events: &[Event],
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> maud::Markup {
+    crate::view::authenticated(
+        username,
+        Some(
+            format!(
+                "{} - {} Pick History",
+                book_subscription.name, chapter.title
+            )
+            .as_str(),
+        ),
+        None,
+        None,
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a href="../.." class="text-blue-400 hover:underline" { (book_subscription.name) } " > "
+                a href="." class="text-blue-400 hover:underline" { (chapter.title) } " > "
+                a {"Pick History"}
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center gap-4" {
+                h1 class="text-4xl font-extrabold" { "My Pick History" }
+                (timeline(revisions, events, teams))
+            }
+        }),
+        None,
+    )
+}
+
+/// Admin view of any member's history, swapped in place when another member
+/// is picked
+pub fn picker(
+    users: &[(i32, String)],
+    selected: Option<i32>,
+    revisions: &[PickRevision],
+    events: &[Event],
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> maud::Markup {
+    maud::html! {
+        div class="flex flex-col items-center gap-2" {
+            form hx-get="revisions" hx-trigger="change" hx-target="closest div" hx-swap="outerHTML" {
+                select name="user_id" class="border border-green-300 rounded" {
+                    option value="" selected[selected.is_none()] { "Choose a member" }
+                    @for (id, username) in users {
+                        option value=(id) selected[selected == Some(*id)] { (username) }
+                    }
+                }
+            }
+            @if selected.is_some() {
+                (timeline(revisions, events, teams))
+            }
+        }
+    }
+}
+
+fn timeline(
+    revisions: &[PickRevision],
+    events: &[Event],
+    teams: &HashMap<i32, (String, Option<String>)>,
+) -> maud::Markup {
+    let team = |id: &i32| teams.get(id).map_or("Unknown", |(name, _)| name.as_str());
+
+    maud::html! {
+        ol class="w-full max-w-xl border-l-2 border-green-300" {
+            @if revisions.is_empty() {
+                li class="ml-4 text-gray-500" { "No picks submitted" }
+            }
+            @for ((submitted_at, entered_by), submission) in &revisions.iter().chunk_by(|revision| (revision.submitted_at.as_str(), revision.entered_by.as_deref())) {
+                li class="mb-4 ml-4" {
+                    p class="text-sm font-semibold text-gray-600" {
+                        (submitted_at)
+                        @if let Some(entered_by) = entered_by {
+                            span class="font-normal text-gray-500" { " · 📞 entered by " (entered_by) }
+                        }
+                    }
+                    ul class="text-sm" {
+                        @for revision in submission {
+                            @match events.iter().find(|event| event.id == revision.event_id).map(|event| &event.contents.0) {
+                                Some(EventContent::SpreadGroup(spreads)) => {
+                                    @let choices = serde_json::from_value::<Vec<String>>(revision.choice.clone()).unwrap_or_default();
+                                    @let wagers = serde_json::from_value::<Vec<i32>>(revision.wager.clone()).unwrap_or_default();
+                                    @for ((spread, choice), wager) in spreads.iter().zip(&choices).zip(&wagers) {
+                                        li {
+                                            (team(&spread.away_id)) " @ " (team(&spread.home_id)) ": "
+                                            span class="font-semibold" {
+                                                @if choice == "home" { (team(&spread.home_id)) } @else { (team(&spread.away_id)) }
+                                            }
+                                            " (" (wager) ")"
+                                        }
+                                    }
+                                }
+                                Some(EventContent::UserInput(input)) => {
+                                    li {
+                                        (input.title) ": "
+                                        span class="font-semibold" { (revision.choice.as_str().unwrap_or_default()) }
+                                    }
+                                }
+                                None => {}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
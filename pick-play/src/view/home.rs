@@ -1,13 +1,21 @@
-use crate::{model::book::BookSubscriptionStats, view::book_list};
+use crate::{
+    model::{book::BookSubscriptionStats, join_request::JoinRequest},
+    view::{book_list, join_request},
+};
 
 use super::authenticated;
 
-pub fn m(username: &str, is_admin: bool, books: Vec<BookSubscriptionStats>) -> maud::Markup {
+pub fn m(
+    username: &str,
+    is_admin: bool,
+    books: Vec<BookSubscriptionStats>,
+    join_requests: &[JoinRequest],
+) -> maud::Markup {
     authenticated(
         username,
         Some("Home"),
         None,
-        None,
+        Some(super::alertify()),
         Some(maud::html! {
              p {
                 a {"Home"}
@@ -23,6 +31,14 @@ pub fn m(username: &str, is_admin: bool, books: Vec<BookSubscriptionStats>) -> m
                     button type="submit" class="button" { "Create" }
                 }
             }
+            form hx-post="/join" class="my-2" {
+                label {
+                    p {"Join a Book"}
+                    input type="text" placeholder="Join Code" maxlength="8" name="code" required class="uppercase";
+                }
+                button type="submit" class="button" { "Join" }
+            }
+            (join_request::user_requests(join_requests))
             h3 class="h-3" { b { "Your Books"}}
             (book_list::markup(books))
         }),
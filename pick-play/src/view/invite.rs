@@ -0,0 +1,22 @@
+use super::authenticated;
+
+pub fn invalid(username: &str) -> maud::Markup {
+    authenticated(
+        username,
+        Some("Invalid Invite"),
+        None,
+        None,
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"}
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center pt-10" {
+                p class="text-lg font-semibold" { "This invite is invalid or has expired" }
+                p { "Ask the book's admin for a new link" }
+            }
+        }),
+        None,
+    )
+}
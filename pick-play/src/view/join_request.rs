@@ -0,0 +1,100 @@
+use crate::model::join_request::JoinRequest;
+
+use super::authenticated;
+
+pub fn m(username: &str, book_id: i32, book_name: &str) -> maud::Markup {
+    authenticated(
+        username,
+        Some(book_name),
+        None,
+        Some(super::alertify()),
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a { (book_name) }
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center pt-10" {
+                h1 class="text-4xl font-extrabold" { (book_name) }
+                p class="my-3" { "You aren't a member of this book yet" }
+                button
+                    hx-post={"/join-request/"(book_id)}
+                    hx-on--after-request="if (event.detail.successful) this.disabled = true"
+                    class="px-3 py-2 font-bold text-white bg-green-600 rounded disabled:bg-gray-400 hover:bg-green-700" {
+                    "Request to Join"
+                }
+            }
+        }),
+        None,
+    )
+}
+
+pub fn user_requests(requests: &[JoinRequest]) -> maud::Markup {
+    maud::html! {
+        @if !requests.is_empty() {
+            ul class="flex flex-col items-center my-2" {
+                @for request in requests {
+                    li class="flex items-center justify-between p-2 my-1 bg-white border border-gray-300 rounded-lg shadow-sm w-60" hx-target="this" hx-swap="outerHTML" {
+                        span class="font-medium" { (request.book_name) }
+                        @match request.status.as_str() {
+                            "approved" => {
+                                a href={"/book/"(request.book_id)"/"} class="text-green-500 hover:underline" { "Approved" }
+                            },
+                            "rejected" => {
+                                span class="text-red-500" { "Rejected" }
+                            },
+                            _ => {
+                                span class="text-orange-500" { "Pending" }
+                            },
+                        }
+                        @if request.status != "pending" {
+                            button hx-delete={"/join-requests/"(request.id)} class="text-gray-500 hover:text-gray-900" { "✕" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn pending_queue(book_id: i32, requests: &[JoinRequest]) -> maud::Markup {
+    maud::html! {
+        div class="flex justify-center my-6" {
+            fieldset class="p-3 border border-green-600" {
+                legend class="ml-3" { "Join Requests" }
+                p class="text-sm" {
+                    "Anyone with this link can ask to join: "
+                    a href={"/join-request/"(book_id)} class="font-mono text-blue-400 hover:underline" { "/join-request/"(book_id) }
+                }
+                @if requests.is_empty() {
+                    p class="mt-2 text-sm text-gray-500" { "No pending requests" }
+                } @else {
+                    table class="w-full mt-3 text-sm text-left text-gray-500" {
+                        tbody {
+                            @for request in requests {
+                                tr class="bg-white" hx-target="this" hx-swap="outerHTML" {
+                                    td class="px-3 py-2 font-medium text-gray-900" { (request.username) }
+                                    td class="px-3 py-2" {
+                                        button
+                                            hx-post="join-request"
+                                            hx-vals={r#"{"request_id":""#(request.id)r#"","approve":"true"}"#}
+                                            class="px-2 py-1 mr-1 font-bold text-white bg-green-600 rounded hover:bg-green-700" {
+                                            "Approve"
+                                        }
+                                        button
+                                            hx-post="join-request"
+                                            hx-vals={r#"{"request_id":""#(request.id)r#"","approve":"false"}"#}
+                                            class="px-2 py-1 font-bold text-white bg-orange-600 rounded hover:bg-orange-700" {
+                                            "Reject"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
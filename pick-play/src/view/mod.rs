@@ -1,15 +1,20 @@
 use maud::{html, Markup, DOCTYPE};
 
+pub mod achievement;
 pub mod add_event;
 pub mod book;
 pub mod book_list;
 pub mod chapter;
 pub mod finish_signup;
 pub mod home;
+pub mod invite;
+pub mod join_request;
 pub mod legacy_login;
 pub mod login;
 pub mod player_rankings;
+pub mod profile;
 pub mod signup;
+pub mod team;
 pub mod team_search;
 pub mod team_select;
 
@@ -1,14 +1,14 @@
 use maud::{html, Markup};
 use crate::model::player_ranking::PlayerRanking;
 
-pub fn player_rankings_card(rankings: Vec<PlayerRanking>) -> Markup {
+pub fn player_rankings_card(rankings: &[PlayerRanking]) -> Markup {
     html! {
         // Card Container
         div class="bg-white rounded-lg border border-gray-200 shadow-sm overflow-hidden" {
             // Card Header
             div class="px-6 py-4 border-b border-gray-100" {
                 h3 class="text-lg font-semibold text-gray-900" { "Player Rankings" }
-                p class="text-sm text-gray-600 mt-1" { "Overall performance across all questions" }
+                p class="text-sm text-gray-600 mt-1" { "Overall performance across all graded games and questions" }
             }
             
             // Card Content
@@ -22,8 +22,11 @@ pub fn player_rankings_card(rankings: Vec<PlayerRanking>) -> Markup {
                                 tr {
                                     th class="w-20 px-4 py-3 text-center text-xs font-medium text-gray-500 uppercase tracking-wider" { "Rank" }
                                     th class="px-4 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider" { "Player" }
-                                    th class="px-4 py-3 text-center text-xs font-medium text-gray-500 uppercase tracking-wider" { "Correct" }
+                                    th class="px-4 py-3 text-center text-xs font-medium text-gray-500 uppercase tracking-wider" { "Spreads" }
+                                    th class="px-4 py-3 text-center text-xs font-medium text-gray-500 uppercase tracking-wider" { "Questions" }
                                     th class="px-4 py-3 text-center text-xs font-medium text-gray-500 uppercase tracking-wider" { "Accuracy" }
+                                    th class="px-4 py-3 text-center text-xs font-medium text-gray-500 uppercase tracking-wider" { "Confidence (W / L)" }
+                                    th class="px-4 py-3 text-center text-xs font-medium text-gray-500 uppercase tracking-wider" { "Pts / Chapter" }
                                     th class="px-4 py-3 text-right text-xs font-medium text-gray-500 uppercase tracking-wider" { "Total Score" }
                                 }
                             }
@@ -58,9 +61,12 @@ pub fn player_rankings_card(rankings: Vec<PlayerRanking>) -> Markup {
                                             }
                                         }
                                         
-                                        // Correct Answers Column
-                                        td class="px-4 py-4 text-center text-sm text-gray-900" { 
-                                            (player.correct_guesses) " / " (player.total_guesses)
+                                        // Correct Picks Columns
+                                        td class="px-4 py-4 text-center text-sm text-gray-900" {
+                                            (player.spread_correct) " / " (player.spread_total)
+                                        }
+                                        td class="px-4 py-4 text-center text-sm text-gray-900" {
+                                            (player.question_correct) " / " (player.question_total)
                                         }
                                         
                                         // Accuracy Column with Percentage Styling
@@ -78,6 +84,15 @@ pub fn player_rankings_card(rankings: Vec<PlayerRanking>) -> Markup {
                                             }
                                         }
                                         
+                                        // Average Wager on Wins vs Losses
+                                        td class="px-4 py-4 text-center text-sm text-gray-900" {
+                                            (confidence(player.avg_confidence_wins)) " / " (confidence(player.avg_confidence_losses))
+                                        }
+
+                                        td class="px-4 py-4 text-center text-sm text-gray-900" {
+                                            (format!("{:.1}", player.points_per_chapter()))
+                                        }
+
                                         // Total Score Column
                                         td class="px-4 py-4 text-right text-sm font-bold text-lg text-gray-900" { 
                                             (player.score)
@@ -93,3 +108,6 @@ pub fn player_rankings_card(rankings: Vec<PlayerRanking>) -> Markup {
     }
 }
 
+fn confidence(average: Option<f64>) -> String {
+    average.map_or("-".to_string(), |average| format!("{average:.1}"))
+}
@@ -0,0 +1,136 @@
+use crate::model::profile::{current_streak, ChapterResult, SharedBook, SpreadResult, Streak};
+
+use super::authenticated;
+
+pub fn m(
+    viewer_username: &str,
+    username: &str,
+    books: &[SharedBook],
+    chapters: &[ChapterResult],
+    spreads: &[SpreadResult],
+) -> maud::Markup {
+    authenticated(
+        viewer_username,
+        Some(username),
+        None,
+        None,
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a { (username) }
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center gap-4" {
+                h1 class="text-4xl font-extrabold" { (username) }
+                @if books.is_empty() {
+                    p class="text-gray-500" { "You don't share any books with " (username) }
+                } @else {
+                    div class="flex gap-4" {
+                        (stat("Against the Spread", ats_record(spreads.iter())))
+                        (stat("Current Streak", streak(current_streak(spreads.iter()))))
+                        (stat("Shared Books", books.len().to_string()))
+                    }
+                    @for book in books {
+                        (book_card(
+                            book,
+                            chapters.iter().filter(|chapter| chapter.book_id == book.id).collect(),
+                            spreads.iter().filter(|spread| spread.book_id == book.id).collect(),
+                        ))
+                    }
+                }
+            }
+        }),
+        None,
+    )
+}
+
+fn stat(label: &str, value: String) -> maud::Markup {
+    maud::html! {
+        div class="flex flex-col items-center p-3 bg-white border border-gray-200 rounded-lg shadow-sm" {
+            span class="text-xs text-gray-500 uppercase" { (label) }
+            span class="text-lg font-bold" { (value) }
+        }
+    }
+}
+
+fn ats_record<'a>(spreads: impl Iterator<Item = &'a SpreadResult>) -> String {
+    let (wins, total) = spreads.fold((0, 0), |(wins, total), spread| {
+        (wins + spread.correct as i32, total + 1)
+    });
+
+    if total == 0 {
+        "-".to_string()
+    } else {
+        format!(
+            "{wins}-{} ({:.0}%)",
+            total - wins,
+            wins as f64 / total as f64 * 100.0
+        )
+    }
+}
+
+fn streak(streak: Option<Streak>) -> String {
+    match streak {
+        Some(Streak {
+            correct: true,
+            length,
+        }) => format!("W{length}"),
+        Some(Streak {
+            correct: false,
+            length,
+        }) => format!("L{length}"),
+        None => "-".to_string(),
+    }
+}
+
+fn book_card(
+    book: &SharedBook,
+    chapters: Vec<&ChapterResult>,
+    spreads: Vec<&SpreadResult>,
+) -> maud::Markup {
+    let best = chapters.iter().max_by_key(|chapter| chapter.points);
+    let worst = chapters.iter().min_by_key(|chapter| chapter.points);
+
+    maud::html! {
+        div class="w-full max-w-xl p-4 bg-white border border-gray-200 rounded-lg shadow-sm" {
+            a href={"/book/"(book.id)"/"} class="text-lg font-semibold text-blue-400 hover:underline" { (book.name) }
+            @if chapters.is_empty() {
+                p class="text-gray-500" { "No graded chapters yet" }
+            } @else {
+                div class="flex flex-wrap gap-4 my-2 text-sm" {
+                    span { "ATS: " (ats_record(spreads.iter().copied())) }
+                    span { "Streak: " (streak(current_streak(spreads.iter().copied()))) }
+                    @if let Some(best) = best {
+                        span class="text-green-600" { "Best: " (best.title) " (" (best.points) ")" }
+                    }
+                    @if let Some(worst) = worst {
+                        span class="text-red-600" { "Worst: " (worst.title) " (" (worst.points) ")" }
+                    }
+                }
+                table class="w-full text-sm text-left text-gray-500" {
+                    thead class="text-xs text-gray-700 uppercase bg-gray-100" {
+                        tr {
+                            th scope="col" class="px-3 py-2" { "Chapter" }
+                            th scope="col" class="px-3 py-2" { "Points" }
+                            th scope="col" class="px-3 py-2" { "Total" }
+                            th scope="col" class="px-3 py-2" { "Rank" }
+                        }
+                    }
+                    tbody {
+                        @for chapter in &chapters {
+                            tr class="bg-white" {
+                                td class="px-3 py-2" {
+                                    a href={"/book/"(book.id)"/chapter/"(chapter.chapter_id)"/"} class="text-blue-400 hover:underline" { (chapter.title) }
+                                }
+                                td class="px-3 py-2" { (chapter.points) }
+                                td class="px-3 py-2" { (chapter.total_points) }
+                                td class="px-3 py-2" { "#" (chapter.rank) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::model::{
+    book::BookSubscription,
+    team::{AtsResult, Team, TeamGame},
+};
+
+pub fn m(
+    username: &str,
+    book_subscription: &BookSubscription,
+    team: &Team,
+    games: &[TeamGame],
+) -> maud::Markup {
+    let count = |result| {
+        games
+            .iter()
+            .filter(|game| game.result() == Some(result))
+            .count()
+    };
+    let (covers, losses, pushes) = (
+        count(AtsResult::Cover),
+        count(AtsResult::Loss),
+        count(AtsResult::Push),
+    );
+    let average_spread = (!games.is_empty())
+        .then(|| games.iter().map(TeamGame::spread).sum::<f64>() / games.len() as f64);
+
+    let times_picked = games.iter().map(|game| game.times_picked).sum::<i32>();
+    let picked_right = games
+        .iter()
+        .filter(|game| game.result() == Some(AtsResult::Cover))
+        .map(|game| game.times_picked)
+        .sum::<i32>();
+
+    crate::view::authenticated(
+        username,
+        Some(format!("{} - {}", book_subscription.name, team.name).as_str()),
+        None,
+        None,
+        Some(maud::html! {
+            p {
+                a href="/" class="text-blue-400 hover:underline" {"Home"} " > "
+                a href={"/book/"(book_subscription.id)"/"} class="text-blue-400 hover:underline" { (book_subscription.name) } " > "
+                a { (team.name) }
+            }
+        }),
+        Some(maud::html! {
+            div class="flex flex-col items-center justify-center gap-4" {
+                div class="flex items-center gap-3" {
+                    @if let Some(logo) = &team.logo {
+                        img src=(logo) class="object-scale-down w-12 h-12" alt="";
+                    }
+                    h1 class="text-4xl font-extrabold" { (team.name) }
+                }
+                div class="flex flex-wrap justify-center gap-4" {
+                    (stat("ATS Record", format!("{covers}-{losses}-{pushes}")))
+                    (stat("Average Spread", average_spread.map_or("-".to_string(), |spread| format!("{spread:+.1}"))))
+                    (stat("Times Picked", times_picked.to_string()))
+                    (stat("Picked & Covered", if times_picked > 0 {
+                        format!("{picked_right} ({:.0}%)", picked_right as f64 / times_picked as f64 * 100.0)
+                    } else {
+                        "-".to_string()
+                    }))
+                }
+                table class="w-full max-w-2xl text-sm text-left text-gray-500" {
+                    thead class="text-xs text-gray-700 uppercase bg-gray-100" {
+                        tr {
+                            th scope="col" class="px-3 py-2" { "Chapter" }
+                            th scope="col" class="px-3 py-2" { "Opponent" }
+                            th scope="col" class="px-3 py-2" { "Spread" }
+                            th scope="col" class="px-3 py-2" { "Result" }
+                            th scope="col" class="px-3 py-2" { "Picked" }
+                        }
+                    }
+                    tbody {
+                        @if games.is_empty() {
+                            tr class="bg-white" {
+                                td colspan="5" class="px-3 py-2 text-center" { "No games in closed chapters yet" }
+                            }
+                        }
+                        @for game in games {
+                            tr class="bg-white" {
+                                td class="px-3 py-2" {
+                                    a href={"/book/"(book_subscription.id)"/chapter/"(game.chapter_id)"/"} class="text-blue-400 hover:underline" { (game.chapter_title) }
+                                }
+                                td class="px-3 py-2" {
+                                    @if game.is_home { "vs " } @else { "@ " }
+                                    a href={"/book/"(book_subscription.id)"/team/"(game.opponent_id)} class="text-blue-400 hover:underline" { (game.opponent_name) }
+                                }
+                                td class="px-3 py-2" { (format!("{:+}", game.spread())) }
+                                @match game.result() {
+                                    Some(AtsResult::Cover) => td class="px-3 py-2 font-bold text-green-600" { "Covered" },
+                                    Some(AtsResult::Loss) => td class="px-3 py-2 font-bold text-red-600" { "Lost" },
+                                    Some(AtsResult::Push) => td class="px-3 py-2 font-bold text-orange-600" { "Push" },
+                                    None => td class="px-3 py-2" { "Pending" },
+                                }
+                                td class="px-3 py-2" { (game.times_picked) " / " (game.total_picks) }
+                            }
+                        }
+                    }
+                }
+            }
+        }),
+        None,
+    )
+}
+
+fn stat(label: &str, value: String) -> maud::Markup {
+    maud::html! {
+        div class="flex flex-col items-center p-3 bg-white border border-gray-200 rounded-lg shadow-sm" {
+            span class="text-xs text-gray-500 uppercase" { (label) }
+            span class="text-lg font-bold" { (value) }
+        }
+    }
+}